@@ -1,50 +1,67 @@
+mod sim;
+
+use std::cell::RefCell;
+
 use lyrebird_renderer::prelude::{winit::keyboard::KeyCode, *};
+use lyrebird_runtime::RuntimeScene;
+
+use sim::SimControl;
+
+thread_local! {
+    // the editor is a Slint component, which can't hold the simulation state itself.
+    static SIM: RefCell<SimControl> = RefCell::default();
+    static SCENE: RefCell<RuntimeScene> = RefCell::default();
+}
 
 impl AppBehaviour for crate::LyrebirdEditor {
-    fn new() -> Self {
-        Self::new().unwrap()
+    type Args = ();
+
+    fn new(_args: ()) -> Self {
+        let editor = Self::new().unwrap();
+        editor.on_toggle_play(|| SIM.with_borrow_mut(SimControl::toggle));
+        editor.on_step(|| SIM.with_borrow_mut(SimControl::step));
+        editor
     }
 
-    fn init(&mut self, _ctx: Context) {
+    fn init(&mut self, ctx: &Context) {
         // ctx.graphics.window.set_title("lyrebird editor");
+        SCENE.with_borrow_mut(|scene| scene.init(ctx));
     }
     
-    fn update(&mut self, ctx: Context, _dt: f64) {
+    fn update(&mut self, ctx: &Context, dt: f64) {
         if ctx.input.is_key_down(KeyCode::Escape) 
             || ctx.input.gamepads_snapshot().gamepads.iter().find(|(_, state)| state.buttons_down.contains(&gilrs::Button::Start)).is_some()
         {
-            
+            ctx.request_exit();
         }
-    }
-    
-    fn render(&mut self, ctx: Context, view: &wgpu::TextureView) {
-        let mut encoder = ctx.graphics.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
-            label: Some("Render Encoder"),
-        });
 
-        {
-            let _render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.1,
-                            g: 0.2,
-                            b: 0.3,
-                            a: 1.0,
-                        }),
-                        store: wgpu::StoreOp::Store,
-                    },
-                    depth_slice: None,
-                })],
-                depth_stencil_attachment: None,
-                occlusion_query_set: None,
-                timestamp_writes: None,
+        // the editor itself keeps updating; only the scene is gated, and rendering carries on so
+        // a paused scene stays on screen.
+        let (sim_dt, playing) = SIM.with_borrow_mut(|sim| (sim.advance(dt), sim.is_playing()));
+        self.set_playing(playing);
+        if let Some(sim_dt) = sim_dt {
+            let time = SCENE.with_borrow_mut(|scene| {
+                scene.update(ctx, sim_dt);
+                scene.time()
             });
+            self.set_sim_time(time as f32);
         }
+    }
+    
+    fn render(&mut self, ctx: &Context, view: &wgpu::TextureView) {
+        SCENE.with_borrow_mut(|scene| scene.render(ctx, view));
+    }
+
+    fn viewport_image(&mut self, image: slint::Image) {
+        self.set_texture(image);
+    }
 
-        ctx.graphics.queue.submit(std::iter::once(encoder.finish()));
+    fn viewport_rect(&self) -> Option<ViewportRect> {
+        Some(ViewportRect::new(
+            self.get_viewport_x(),
+            self.get_viewport_y(),
+            self.get_viewport_width(),
+            self.get_viewport_height(),
+        ))
     }
-}
\ No newline at end of file
+}
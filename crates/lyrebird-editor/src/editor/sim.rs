@@ -0,0 +1,93 @@
+/// Whether the scene being edited is simulating.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SimState {
+    Playing,
+    /// The scene keeps rendering, frozen on its last frame.
+    #[default]
+    Paused,
+}
+
+/// Play, pause and single-step controls for the simulation, deciding each frame whether the
+/// scene gets updated and by how much.
+#[derive(Debug, Clone)]
+pub struct SimControl {
+    state: SimState,
+    step_requested: bool,
+    /// How far a single step advances the simulation, in seconds.
+    pub fixed_step: f64,
+}
+
+impl Default for SimControl {
+    fn default() -> Self {
+        Self {
+            state: SimState::default(),
+            step_requested: false,
+            fixed_step: 1.0 / 60.0,
+        }
+    }
+}
+
+impl SimControl {
+    pub fn is_playing(&self) -> bool {
+        self.state == SimState::Playing
+    }
+
+    pub fn toggle(&mut self) {
+        self.state = match self.state {
+            SimState::Playing => SimState::Paused,
+            SimState::Paused => SimState::Playing,
+        };
+    }
+
+    /// Pauses if needed and advances by exactly one [Self::fixed_step] on the next frame.
+    pub fn step(&mut self) {
+        self.state = SimState::Paused;
+        self.step_requested = true;
+    }
+
+    /// Called once per frame with the real frame time. Returns the time to update the scene by,
+    /// or `None` if it shouldn't be updated this frame.
+    pub fn advance(&mut self, dt: f64) -> Option<f64> {
+        match self.state {
+            SimState::Playing => {
+                self.step_requested = false;
+                Some(dt)
+            }
+            SimState::Paused if std::mem::take(&mut self.step_requested) => Some(self.fixed_step),
+            SimState::Paused => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SimControl;
+
+    #[test]
+    fn paused_skips_updates() {
+        let mut sim = SimControl::default();
+        assert!(!sim.is_playing());
+        assert_eq!(sim.advance(0.1), None);
+        assert_eq!(sim.advance(0.1), None);
+    }
+
+    #[test]
+    fn step_advances_one_fixed_step() {
+        let mut sim = SimControl { fixed_step: 0.25, ..Default::default() };
+        sim.step();
+        assert_eq!(sim.advance(0.1), Some(0.25));
+        assert_eq!(sim.advance(0.1), None);
+    }
+
+    #[test]
+    fn playing_passes_dt_through() {
+        let mut sim = SimControl::default();
+        sim.toggle();
+        assert!(sim.is_playing());
+        assert_eq!(sim.advance(0.1), Some(0.1));
+        assert_eq!(sim.advance(0.03), Some(0.03));
+
+        sim.toggle();
+        assert_eq!(sim.advance(0.1), None);
+    }
+}
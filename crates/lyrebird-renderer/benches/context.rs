@@ -0,0 +1,55 @@
+//! Compares handing a scene the frame's [Context] by reference, as the callbacks do, with cloning
+//! it for every callback, as they used to. Run with `cargo bench -p lyrebird-renderer`.
+
+use std::{hint::black_box, time::{Duration, Instant}};
+
+use lyrebird_renderer::prelude::*;
+
+const CALLS: u32 = 1_000_000;
+
+#[inline(never)]
+fn borrowed(ctx: &Context) -> Duration {
+    black_box(ctx).dt()
+}
+
+#[inline(never)]
+fn owned(ctx: Context) -> Duration {
+    black_box(ctx).dt()
+}
+
+fn time(name: &str, mut f: impl FnMut()) {
+    let start = Instant::now();
+    for _ in 0..CALLS {
+        f();
+    }
+    let elapsed = start.elapsed();
+    println!("{name:>8}: {:>8.2} ns/call", elapsed.as_nanos() as f64 / CALLS as f64);
+}
+
+struct Bench;
+
+impl AppBehaviour for Bench {
+    type Args = ();
+
+    fn new(_args: ()) -> Self {
+        Self
+    }
+
+    fn init(&mut self, _ctx: &Context) {}
+
+    fn update(&mut self, ctx: &Context, _dt: f64) {
+        time("borrowed", || {
+            black_box(borrowed(ctx));
+        });
+        time("cloned", || {
+            black_box(owned(ctx.clone()));
+        });
+    }
+
+    fn render(&mut self, _ctx: &Context, _view: &wgpu::TextureView) {}
+}
+
+fn main() -> anyhow::Result<()> {
+    lyrebird_renderer::run_headless::<Bench>(1, 1, 1)?;
+    Ok(())
+}
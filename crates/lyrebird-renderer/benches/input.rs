@@ -0,0 +1,85 @@
+//! Times a frame's worth of input queries against an [InputManager] that other threads are
+//! also using: readers, which the read lock lets through side by side, and a writer, as the
+//! event loop is. Run with `cargo bench -p lyrebird-renderer --bench input`.
+
+use std::{
+    hint::black_box,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Instant,
+};
+
+use lyrebird_renderer::prelude::{winit::keyboard::KeyCode, *};
+
+const FRAMES: u32 = 100_000;
+
+const KEYS: [KeyCode; 40] = [
+    KeyCode::KeyA, KeyCode::KeyB, KeyCode::KeyC, KeyCode::KeyD, KeyCode::KeyE, KeyCode::KeyF,
+    KeyCode::KeyG, KeyCode::KeyH, KeyCode::KeyI, KeyCode::KeyJ, KeyCode::KeyK, KeyCode::KeyL,
+    KeyCode::KeyM, KeyCode::KeyN, KeyCode::KeyO, KeyCode::KeyP, KeyCode::KeyQ, KeyCode::KeyR,
+    KeyCode::KeyS, KeyCode::KeyT, KeyCode::KeyU, KeyCode::KeyV, KeyCode::KeyW, KeyCode::KeyX,
+    KeyCode::KeyY, KeyCode::KeyZ, KeyCode::Digit0, KeyCode::Digit1, KeyCode::Digit2,
+    KeyCode::Digit3, KeyCode::Digit4, KeyCode::Digit5, KeyCode::Digit6, KeyCode::Digit7,
+    KeyCode::Digit8, KeyCode::Digit9, KeyCode::Space, KeyCode::Escape, KeyCode::Enter,
+    KeyCode::Tab,
+];
+
+/// A frame's input handling done one query at a time, each taking the lock.
+fn queries(input: &InputManager) {
+    for key in KEYS {
+        black_box(input.is_key_down(key));
+    }
+    black_box(input.cursor_position());
+    black_box(input.scroll_delta());
+    black_box(input.mouse_motion());
+    black_box(input.modifiers());
+    black_box(input.is_mouse_down(winit::event::MouseButton::Left));
+    black_box(input.is_mouse_down(winit::event::MouseButton::Right));
+}
+
+/// The same queries under one lock, with [InputManager::with_snapshot].
+fn snapshot(input: &InputManager) {
+    input.with_snapshot(|input| {
+        for key in KEYS {
+            black_box(input.is_key_down(key));
+        }
+        black_box(input.cursor_position());
+        black_box(input.scroll_delta());
+        black_box(input.mouse_motion());
+        black_box(input.modifiers());
+        black_box(input.is_mouse_down(winit::event::MouseButton::Left));
+        black_box(input.is_mouse_down(winit::event::MouseButton::Right));
+    });
+}
+
+fn time(name: &str, input: &InputManager, frame: fn(&InputManager)) {
+    let start = Instant::now();
+    for _ in 0..FRAMES {
+        frame(input);
+    }
+    let elapsed = start.elapsed();
+    println!("{name:>26}: {:>8.1} ns/frame", elapsed.as_nanos() as f64 / FRAMES as f64);
+}
+
+/// Runs both kinds of frame while `other` hammers the same input manager on another thread.
+fn contended(name: &str, input: &InputManager, other: fn(&InputManager)) {
+    let done = AtomicBool::new(false);
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            while !done.load(Ordering::Relaxed) {
+                other(input);
+            }
+        });
+        time(&format!("queries, {name}"), input, queries);
+        time(&format!("snapshot, {name}"), input, snapshot);
+        done.store(true, Ordering::Relaxed);
+    });
+}
+
+fn main() {
+    let input = InputManager::default();
+
+    time("queries, alone", &input, queries);
+    time("snapshot, alone", &input, snapshot);
+    contended("with a reader", &input, queries);
+    contended("with a writer", &input, InputManager::reset_frame_deltas);
+}
@@ -0,0 +1,162 @@
+//! Loads a PNG on a background thread while frames keep rendering, and starts drawing it on
+//! the first frame after it arrives.
+//!
+//! ```sh
+//! cargo run -p lyrebird-renderer --features image --example async_texture -- path/to/image.png
+//! ```
+
+use std::path::PathBuf;
+
+use lyrebird_renderer::{GraphicsContext, State, prelude::*, run_headless_with};
+
+const SHADER: &str = r#"
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOutput;
+    out.position = vec4<f32>(uv * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0), 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+@group(0) @binding(0) var image: texture_2d<f32>;
+@group(0) @binding(1) var image_sampler: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(image, image_sampler, in.uv);
+}
+"#;
+
+struct Blit {
+    pipeline: wgpu::RenderPipeline,
+    bind_group: wgpu::BindGroup,
+}
+
+impl Blit {
+    fn new(graphics: &GraphicsContext, texture: &Texture) -> Self {
+        let device = &graphics.device;
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("blit shader"),
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("blit pipeline"),
+            layout: None,
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                compilation_options: Default::default(),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                compilation_options: Default::default(),
+                targets: &[Some(State::FORMAT.into())],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("blit bind group"),
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(texture.view()),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(texture.sampler()),
+                },
+            ],
+        });
+
+        Self { pipeline, bind_group }
+    }
+}
+
+struct AsyncTexture {
+    path: PathBuf,
+    uploader: Option<AssetUploader>,
+    pending: Option<PendingAsset<Texture>>,
+    blit: Option<Blit>,
+    frame: usize,
+}
+
+impl AppBehaviour for AsyncTexture {
+    type Args = PathBuf;
+
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            uploader: None,
+            pending: None,
+            blit: None,
+            frame: 0,
+        }
+    }
+
+    fn init(&mut self, ctx: &Context) {
+        let uploader = AssetUploader::new(ctx.graphics.clone());
+        self.pending = Some(uploader.load_texture(&self.path));
+        self.uploader = Some(uploader);
+    }
+
+    fn update(&mut self, ctx: &Context, _dt: f64) {
+        self.frame += 1;
+
+        let Some(result) = self.pending.as_ref().and_then(PendingAsset::try_take) else {
+            return;
+        };
+        self.pending = None;
+
+        match result {
+            Ok(texture) => {
+                println!("texture arrived on frame {}", self.frame);
+                self.blit = Some(Blit::new(&ctx.graphics, &texture));
+            }
+            Err(e) => eprintln!("failed to load the texture: {e}"),
+        }
+    }
+
+    fn render(&mut self, ctx: &Context, view: &wgpu::TextureView) {
+        let mut encoder = ctx.graphics.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Render Encoder"),
+        });
+
+        {
+            // until the texture turns up this is just the clear colour.
+            let mut render_pass = ctx.begin_clear_pass(&mut encoder, view);
+            if let Some(blit) = &self.blit {
+                render_pass.set_pipeline(&blit.pipeline);
+                render_pass.set_bind_group(0, &blit.bind_group, &[]);
+                render_pass.draw(0..3, 0..1);
+            }
+        }
+
+        ctx.graphics.submit(encoder);
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    env_logger::init();
+
+    let path = std::env::args()
+        .nth(1)
+        .expect("usage: async_texture <path to image>");
+    let frames = run_headless_with::<AsyncTexture>(path.into(), 256, 256, 120)?;
+    println!("rendered {} frames", frames.len());
+    Ok(())
+}
@@ -0,0 +1,858 @@
+use std::{cell::{Cell, RefCell}, rc::Rc, sync::Arc, time::Duration};
+
+use slint::{ComponentHandle, wgpu_27::{WGPUConfiguration, WGPUSettings}, winit_030::{CustomApplicationHandler, EventLoopBuilder, EventResult, WinitWindowAccessor}};
+use web_time::Instant;
+use winit::{dpi::{PhysicalPosition, PhysicalSize}, event::{DeviceEvent, DeviceId, WindowEvent}, event_loop::{ActiveEventLoop, ControlFlow}};
+
+use crate::{GraphicsContext, State, composite::{Compositor, ViewportFormat}, input::{AxisFiltering, InputManager, ViewportInput}, profiler::GpuProfiler, scene::{AppBehaviour, AppShared, ViewportRect, WindowRequests}, target::RenderTarget, timing::{LatencyProfile, PollStrategy, RedrawMode}, window::{MonitorInfo, PendingResize, WindowConfig, WindowGeometry, set_window_level}};
+
+const DEVICE_LABEL: &str = "lyrebird device";
+
+/// How often gamepads are checked between frames, see [AppBuilder::run_with].
+const GAMEPAD_POLL_INTERVAL: Duration = Duration::from_millis(16);
+/// How long the monitors handed out by [crate::prelude::Context::available_monitors] are kept
+/// before they are read again.
+const MONITOR_REFRESH: Duration = Duration::from_secs(1);
+
+/// Collects everything about how an app is started, finishing with [Self::run].
+///
+/// [crate::run] is the same as `AppBuilder::new().run::<S>()`.
+///
+/// The present mode isn't here because Slint owns the window surface and always configures it
+/// with `AutoVsync`. Use [Self::fps_cap] to limit the frame rate instead.
+///
+/// That also rules out a fallback chain such as "Mailbox, then Fifo, then Immediate": Slint
+/// sets the mode when it configures the surface and there's no hook to change it. `AutoVsync`
+/// is a chain of its own, picking `FifoRelaxed` where the surface supports it and `Fifo`
+/// (which every surface does) otherwise, so every machine ends up vsynced.
+///
+/// Nor is there HDR output. Scenes render into an `Rgba16Float` viewport by default, so values
+/// above 1.0 make it as far as compositing, but Slint configures the window surface itself with
+/// the first non-sRGB format the surface lists, an 8-bit one on every common backend, and the
+/// viewport is clamped to SDR when it is presented.
+///
+/// There's no option for a wgpu API trace (`wgpu::Trace`) either. wgpu 27 has removed tracing
+/// for now (gfx-rs/wgpu#5974), logging an error and carrying on untraced whatever it is asked
+/// for, and on the web Slint creates the device and always passes `Trace::Off`. To
+/// capture frames for a bug report, run the app under a graphics debugger such as RenderDoc,
+/// PIX or Xcode's Metal capture.
+///
+/// Nor can an app hand over a winit `EventLoop` or `Window` it already has, to embed
+/// lyrebird in a larger winit app. Slint builds the event loop and creates every window
+/// itself, and takes no existing ones. [Self::event_loop_builder] reaches as far as Slint
+/// allows. To show lyrebird as a panel, make the rest of the app's UI Slint components
+/// instead, or render headless with [crate::run_headless] and upload the frames.
+pub struct AppBuilder {
+    window: WindowConfig,
+    install_logger: bool,
+    features: wgpu::Features,
+    limits: Option<wgpu::Limits>,
+    max_dt: Option<Duration>,
+    power_preference: wgpu::PowerPreference,
+    fps_cap: Option<u32>,
+    redraw_mode: RedrawMode,
+    poll_strategy: PollStrategy,
+    upscale_filter: wgpu::FilterMode,
+    viewport_format: ViewportFormat,
+    #[cfg(not(target_arch = "wasm32"))]
+    gamepad_mappings: Vec<GamepadMappings>,
+    #[cfg(not(target_arch = "wasm32"))]
+    axis_filtering: AxisFiltering,
+    event_loop_builder: Option<EventLoopBuilder>,
+}
+
+/// Where extra SDL gamepad mappings come from.
+#[cfg(not(target_arch = "wasm32"))]
+enum GamepadMappings {
+    Inline(String),
+    File(std::path::PathBuf),
+}
+
+impl Default for AppBuilder {
+    fn default() -> Self {
+        Self {
+            window: WindowConfig::default(),
+            install_logger: true,
+            features: wgpu::Features::empty(),
+            limits: None,
+            max_dt: Some(crate::DEFAULT_MAX_DT),
+            power_preference: wgpu::PowerPreference::default(),
+            fps_cap: None,
+            redraw_mode: RedrawMode::default(),
+            poll_strategy: PollStrategy::default(),
+            upscale_filter: wgpu::FilterMode::Linear,
+            viewport_format: ViewportFormat::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            gamepad_mappings: Vec::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            axis_filtering: AxisFiltering::default(),
+            event_loop_builder: None,
+        }
+    }
+}
+
+impl AppBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates the window according to `window`.
+    pub fn window(mut self, window: WindowConfig) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Whether to install `env_logger` (or `console_log` on the web), which is on by default.
+    /// Turn this off if the app already sets up `tracing`, `fern` or similar. Even when on, a
+    /// logger that is already installed is kept.
+    pub fn install_logger(mut self, install: bool) -> Self {
+        self.install_logger = install;
+        self
+    }
+
+    /// Device features the scene needs. Startup fails if the adapter doesn't support them.
+    pub fn features(mut self, features: wgpu::Features) -> Self {
+        self.features = features;
+        self
+    }
+
+    /// Device limits the scene needs. Startup fails if the adapter can't meet them, naming the
+    /// ones it falls short of.
+    ///
+    /// Without this, the device gets wgpu's default limits where the adapter can meet them,
+    /// and otherwise the downlevel or WebGL2 downlevel defaults, with a warning. Either way the
+    /// texture size limits are raised to the adapter's own, as the window's swapchain needs.
+    /// On the web, where Slint requests the adapter, it always gets the WebGL2 downlevel ones.
+    /// Scenes can see what they ended up with in [GraphicsContext::limits].
+    pub fn limits(mut self, limits: wgpu::Limits) -> Self {
+        self.limits = Some(limits);
+        self
+    }
+
+    /// Whether to prefer the integrated or the discrete GPU when picking an adapter.
+    pub fn power_preference(mut self, power_preference: wgpu::PowerPreference) -> Self {
+        self.power_preference = power_preference;
+        self
+    }
+
+    /// The frame rate cap to start with. It can be changed later through
+    /// [crate::prelude::Context::set_fps_cap].
+    pub fn fps_cap(mut self, fps: Option<u32>) -> Self {
+        self.fps_cap = fps;
+        self
+    }
+
+    /// Sets the frame rate cap and GPU preference from `profile`. Calling [Self::fps_cap] or
+    /// [Self::power_preference] afterwards overrides that part of it.
+    pub fn latency_profile(mut self, profile: LatencyProfile) -> Self {
+        self.fps_cap = profile.fps_cap();
+        self.power_preference = profile.power_preference();
+        self
+    }
+
+    /// The longest time step a frame is given, 100ms unless set, or `None` for no limit.
+    ///
+    /// Without one, a stall such as a breakpoint, a long load or dragging the window hands the
+    /// next frame a `dt` of seconds, and anything that moves by `velocity * dt` jumps straight
+    /// through walls. The frame is given `max_dt` instead, which
+    /// [crate::prelude::Context::dt_clamped] reports, and the rest of the stall is lost, so the
+    /// scene runs slow rather than catching up. That also bounds how much time
+    /// [AppBehaviour::fixed_update] has to make up in one frame.
+    pub fn max_dt(mut self, max_dt: Option<Duration>) -> Self {
+        self.max_dt = max_dt;
+        self
+    }
+
+    /// Whether to render continuously or only when Slint needs a redraw.
+    pub fn redraw_mode(mut self, mode: RedrawMode) -> Self {
+        self.redraw_mode = mode;
+        self
+    }
+
+    /// How the event loop waits between events and frames. Most apps only need
+    /// [Self::redraw_mode]; this is for keeping the loop polling, or drawing on a fixed interval.
+    pub fn poll_strategy(mut self, strategy: PollStrategy) -> Self {
+        self.poll_strategy = strategy;
+        self
+    }
+
+    /// How a scene rendered below full resolution, through
+    /// [crate::prelude::AppBehaviour::render_scale], is stretched to fill the viewport. `Linear`
+    /// (the default) smooths it out, while `Nearest` keeps hard pixel edges for pixel art.
+    pub fn upscale_filter(mut self, filter: wgpu::FilterMode) -> Self {
+        self.upscale_filter = filter;
+        self
+    }
+
+    /// The format of the view scenes render into, and with it whether the result is
+    /// sRGB-encoded for display. The default is a linear float viewport that is encoded;
+    /// pipelines doing their own gamma want [ViewportFormat::PreferLinear].
+    pub fn viewport_format(mut self, format: ViewportFormat) -> Self {
+        self.viewport_format = format;
+        self
+    }
+
+    /// Adds SDL `gamecontrollerdb` mappings, one per line, for controllers that the database
+    /// built into gilrs gets wrong or doesn't know. Lines starting with `#` are comments. Each
+    /// mapping that loads is logged. Mappings from the `SDL_GAMECONTROLLERCONFIG` environment
+    /// variable are still picked up as well.
+    ///
+    /// The web has no use for these, as the browser maps gamepads itself.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn gamepad_mappings(mut self, mappings: impl Into<String>) -> Self {
+        self.gamepad_mappings.push(GamepadMappings::Inline(mappings.into()));
+        self
+    }
+
+    /// Like [Self::gamepad_mappings], reading them from a `gamecontrollerdb.txt` file when the
+    /// app starts. Starting fails if the file can't be read.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn gamepad_mappings_file(mut self, path: impl Into<std::path::PathBuf>) -> Self {
+        self.gamepad_mappings.push(GamepadMappings::File(path.into()));
+        self
+    }
+
+    /// Whether gilrs applies each gamepad's own stick deadzones, which it does by default, or
+    /// axis values come through unfiltered. See [AxisFiltering].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn gamepad_axis_filtering(mut self, filtering: AxisFiltering) -> Self {
+        self.axis_filtering = filtering;
+        self
+    }
+
+    /// Builds the event loop from `builder` rather than a default one, for the platform options
+    /// it carries, such as running off the main thread with `EventLoopBuilderExtX11::with_any_thread`
+    /// or choosing X11 or Wayland. Slint still creates the loop from it and runs it. The loop's
+    /// event type has to be Slint's own, built with
+    /// `EventLoop::<slint::winit_030::SlintEvent>::with_user_event()`.
+    pub fn event_loop_builder(mut self, builder: EventLoopBuilder) -> Self {
+        self.event_loop_builder = Some(builder);
+        self
+    }
+
+    pub fn run<S>(self) -> anyhow::Result<()>
+    where
+        S: ComponentHandle + AppBehaviour<Args = ()> + 'static,
+    {
+        self.run_with::<S>(())
+    }
+
+    /// Like [Self::run], but constructs the app from `args`.
+    pub fn run_with<S>(self, args: S::Args) -> anyhow::Result<()>
+    where
+        S: ComponentHandle + AppBehaviour + 'static,
+    {
+        let mut settings = WGPUSettings::default();
+        settings.device_required_features = self.features;
+        settings.power_preference = self.power_preference;
+        let window = self.window;
+        let window_limits = window.clone();
+        let redraw_mode = self.redraw_mode;
+        let poll_strategy = self.poll_strategy;
+        let upscale_filter = self.upscale_filter;
+        let viewport_format = self.viewport_format;
+        let max_dt = self.max_dt;
+
+        if self.install_logger {
+            #[cfg(not(target_arch = "wasm32"))]
+            let installed = env_logger::try_init().is_ok();
+            #[cfg(target_arch = "wasm32")]
+            let installed = console_log::init_with_level(log::Level::Info).is_ok();
+
+            if !installed {
+                log::debug!("A logger is already installed, keeping it");
+            }
+        }
+
+        // the present mode is Slint's, so this is everything that decides how frames are paced.
+        log::info!(
+            "Frame pacing: fps cap {:?}, {:?} GPU preferred, {:?} redraws, {:?} event loop, presented with AutoVsync",
+            self.fps_cap,
+            self.power_preference,
+            self.redraw_mode,
+            self.poll_strategy
+        );
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let input_manager = {
+            let mut mappings = String::new();
+            for source in &self.gamepad_mappings {
+                match source {
+                    GamepadMappings::Inline(inline) => mappings.push_str(inline),
+                    GamepadMappings::File(path) => {
+                        let file = std::fs::read_to_string(path).map_err(|e| {
+                            anyhow::anyhow!("failed to read gamepad mappings {}: {e}", path.display())
+                        })?;
+                        mappings.push_str(&file);
+                    }
+                }
+                mappings.push('\n');
+            }
+            InputManager::with_gamepad_config(&mappings, self.axis_filtering)
+        };
+        #[cfg(target_arch = "wasm32")]
+        let input_manager = InputManager::default();
+
+        // the adapter and device are created here and handed to Slint, rather than left to it, so
+        // the adapter the features and limits are checked against is the one that renders, and a
+        // missing adapter is an explanation instead of a panic inside Slint.
+        #[cfg(not(target_arch = "wasm32"))]
+        let (wgpu_config, adapter) = {
+            let (instance, adapter) = request_adapter(&settings)?;
+            let limits = match self.limits {
+                Some(limits) => {
+                    crate::ensure_limits(&limits, &adapter)?;
+                    limits
+                }
+                None => crate::default_limits(&adapter),
+            };
+            // the swapchain is as big as the window, which the downlevel limits' 2048 texels
+            // wouldn't cover on a large monitor, so texture sizes go as high as the adapter's.
+            let limits = limits.using_resolution(adapter.limits());
+            let (device, queue) = crate::request_device(&adapter, DEVICE_LABEL, self.features, limits)?;
+            (WGPUConfiguration::Manual { instance, adapter: adapter.clone(), device, queue }, Some(adapter))
+        };
+        // requesting an adapter is asynchronous in the browser, and this can't wait for it, so
+        // Slint does it there.
+        #[cfg(target_arch = "wasm32")]
+        let (wgpu_config, adapter) = {
+            if let Some(limits) = self.limits {
+                settings.device_required_limits = limits;
+            }
+            (WGPUConfiguration::Automatic(settings), None)
+        };
+
+        let renderer: Rc<RefCell<Option<State>>> = Rc::default();
+        let device_app: Rc<RefCell<Option<S>>> = Rc::default();
+        let mut backend = slint::BackendSelector::new();
+        if let Some(builder) = self.event_loop_builder {
+            backend = backend.with_winit_event_loop_builder(builder);
+        }
+        backend
+            .require_wgpu_27(wgpu_config)
+            .with_winit_window_attributes_hook(move |attributes| window.apply(attributes))
+            .with_winit_custom_application_handler(EventLoopHooks {
+                renderer: renderer.clone(),
+                app: device_app.clone(),
+                poll: poll_strategy == PollStrategy::Poll,
+            })
+            .select()
+            .map_err(|e| anyhow::anyhow!("unable to create the Slint backend with a WGPU renderer: {e}"))?;
+
+        let slint_app = S::new(args);
+        *device_app.borrow_mut() = Some(slint_app.clone_strong());
+
+        let start = Instant::now();
+        let mut last_frame = start;
+        let mut viewport: Option<RenderTarget> = None;
+        let mut compositor: Option<Compositor> = None;
+        let mut shutting_down = false;
+        let mut monitors_read: Option<Instant> = None;
+        // shared between the states made before and after a device loss, like the input.
+        let shared: Arc<AppShared> = Arc::default();
+        shared.limiter.set_target(self.fps_cap);
+
+        #[cfg(feature = "egui")]
+        let egui: Rc<RefCell<crate::egui_layer::EguiLayer>> = Rc::default();
+
+        let mut app = slint_app.clone_strong();
+        let exit_renderer = renderer.clone();
+        let events_renderer = renderer.clone();
+        #[cfg(feature = "egui")]
+        let events_egui = egui.clone();
+        let mut viewport_input = ViewportInput::default();
+        let events_limits = window_limits.clone();
+        let pending_size: Rc<PendingResize> = Rc::default();
+        let events_pending_size = pending_size.clone();
+        slint_app.window().on_winit_window_event(move |window, event| {
+            let renderer = events_renderer.borrow();
+            let Some(state) = renderer.as_ref() else {
+                return EventResult::Propagate;
+            };
+
+            if let WindowEvent::Resized(size) = event {
+                events_pending_size.resized(&events_limits, *size, window.scale_factor() as f64);
+            }
+            if matches!(event, WindowEvent::Resized(_) | WindowEvent::Moved(_)) {
+                let mut geometry = state.shared.window_geometry.lock();
+                if let Some(read) = window.with_winit_window(|winit_window| WindowGeometry::read(winit_window, *geometry)) {
+                    *geometry = Some(read);
+                }
+            }
+
+            if app.window_event(&state.context(), event) {
+                return EventResult::PreventDefault;
+            }
+
+            // whatever falls outside the viewport is only for Slint.
+            let Some(event) = viewport_input.route(viewport_bounds(&app), event) else {
+                return EventResult::Propagate;
+            };
+
+            #[cfg(feature = "egui")]
+            if events_egui.borrow_mut().on_window_event(window, &event) {
+                return EventResult::PreventDefault;
+            }
+
+            state.input_manager.poll(event);
+            EventResult::Propagate
+        });
+
+        // Slint stops rendering while the window is minimised or occluded, and in
+        // `RedrawMode::OnDemand` whenever nothing changed, so gamepads are pumped on a timer
+        // rather than only once a frame. Anything they report asks for a redraw, so the scene
+        // gets to see a "resume" press as soon as it can draw again. Keyboard and mouse input
+        // arrive as window events, which keep coming whether or not frames are drawn.
+        // without frames to reset them, the gamepads' per-frame limits would stop the backlog
+        // from draining, so a tick with no frame drawn since the last one starts a new split.
+        let frame_drawn: Rc<Cell<bool>> = Rc::default();
+        let gamepad_timer = slint::Timer::default();
+        let gamepad_renderer = renderer.clone();
+        let gamepad_frame_drawn = frame_drawn.clone();
+        let mut gamepad_app = slint_app.clone_strong();
+        gamepad_timer.start(slint::TimerMode::Repeated, GAMEPAD_POLL_INTERVAL, move || {
+            let Ok(renderer) = gamepad_renderer.try_borrow() else {
+                return;
+            };
+            let Some(state) = renderer.as_ref() else {
+                return;
+            };
+            if !gamepad_frame_drawn.replace(false) {
+                state.input_manager.split_idle_gamepad_events();
+            }
+            if state.update_gamepads(&mut gamepad_app) {
+                gamepad_app.window().request_redraw();
+            }
+        });
+
+        // with `PollStrategy::Interval`, frames come from this rather than from each other.
+        let interval_timer = slint::Timer::default();
+        if let PollStrategy::Interval(interval) = poll_strategy {
+            let interval_app = slint_app.as_weak();
+            interval_timer.start(slint::TimerMode::Repeated, interval, move || {
+                if let Some(app) = interval_app.upgrade() {
+                    app.window().request_redraw();
+                }
+            });
+        }
+
+        let mut app = slint_app.clone_strong();
+        slint_app.window().set_rendering_notifier(move |state, api| {
+            let mut renderer = renderer.borrow_mut();
+            match state {
+                slint::RenderingState::RenderingSetup => {
+                    if let slint::GraphicsAPI::WGPU27 { instance, device, queue, .. } = api {
+                        let (width, height) = viewport_size(&app, &window_limits);
+                        let render_scale = render_scale(&app);
+                        let ctx = Arc::new(GraphicsContext::new(instance.clone(), adapter.clone(), device.clone(), queue.clone()));
+                        let render_size = scale_size(&ctx, (width, height), render_scale);
+                        let format = viewport_format.resolve(&ctx);
+                        let state = State {
+                            profiler: GpuProfiler::new(&ctx),
+                            ctx,
+                            input_manager: input_manager.clone(),
+                            start,
+                            dt: Duration::ZERO,
+                            max_dt,
+                            dt_clamped: false,
+                            shared: shared.clone(),
+                            encoders: Default::default(),
+                            clear_color: app.clear_color(),
+                            viewport_size: render_size,
+                            render_scale,
+                            surface_ready: false,
+                            fixed_accumulator: Duration::ZERO,
+                            interpolation_alpha: 1.0,
+                            viewport_format: format,
+                        };
+
+                        app.window().with_winit_window(|winit_window| {
+                            window_limits.keep_on_screen(winit_window);
+                            *shared.window_geometry.lock() = Some(WindowGeometry::read(winit_window, window_limits.geometry()));
+                            *shared.monitors.lock() = MonitorInfo::read_all(winit_window);
+                        });
+                        monitors_read = Some(Instant::now());
+                        log::info!("Scenes render into {:?}", state.viewport_format);
+                        app.init(&state.context());
+
+                        #[cfg(feature = "egui")]
+                        egui.borrow_mut().attach(app.window(), &state.ctx, state.viewport_format);
+
+                        *renderer = Some(state);
+                    }
+                },
+                slint::RenderingState::BeforeRendering => {
+                    pending_size.apply(|clamped| {
+                        app.window().with_winit_window(|winit_window| winit_window.request_inner_size(clamped));
+                    });
+
+                    // winit doesn't say when monitors are plugged in or out, so they are read
+                    // again every so often.
+                    if monitors_read.is_none_or(|read| read.elapsed() >= MONITOR_REFRESH) {
+                        if let Some(monitors) = app.window().with_winit_window(MonitorInfo::read_all) {
+                            *shared.monitors.lock() = monitors;
+                        }
+                        monitors_read = Some(Instant::now());
+                    }
+
+                    // a lost device is replaced here and now, from the same adapter, rather than
+                    // waiting for Slint to set up rendering again, which it never does.
+                    if let Some(state) = renderer.as_mut().filter(|state| state.ctx.is_lost()) {
+                        // everything created from the old device is dead, including the viewport.
+                        viewport = None;
+                        compositor = None;
+                        match state.recreate_device(DEVICE_LABEL) {
+                            Ok(()) => {
+                                log::info!("Recovered from device loss");
+                                #[cfg(feature = "egui")]
+                                egui.borrow_mut().attach(app.window(), &state.ctx, state.viewport_format);
+                                app.device_lost(&state.context());
+                            }
+                            Err(e) => {
+                                if !shutting_down {
+                                    log::error!("Shutting down, the device was lost and can't be replaced: {e}");
+                                    shutting_down = true;
+                                    let _ = slint::quit_event_loop();
+                                }
+                                return;
+                            }
+                        }
+                    }
+
+                    if let Some(state) = renderer.as_ref().filter(|state| state.ctx.is_out_of_memory()) {
+                        if !shutting_down {
+                            log::error!("Shutting down, the GPU ran out of memory");
+                            app.out_of_memory(&state.context());
+                            shutting_down = true;
+                            let _ = slint::quit_event_loop();
+                        }
+                        return;
+                    }
+
+                    if let Some(state) = renderer.as_mut() {
+                        let now = Instant::now();
+                        let frame_time = now.duration_since(last_frame);
+                        last_frame = now;
+
+                        state.update_gamepads(&mut app);
+                        let (width, height) = viewport_size(&app, &window_limits);
+                        state.input_manager.set_window_metrics(
+                            app.window().scale_factor() as f64,
+                            PhysicalSize::new(width, height),
+                        );
+                        let dt = state.input_manager.begin_frame(frame_time);
+                        state.set_dt(dt);
+                        state.clear_color = app.clear_color();
+                        state.render_scale = render_scale(&app);
+                        state.viewport_size = scale_size(&state.ctx, (width, height), state.render_scale);
+                        let (render_width, render_height) = state.viewport_size;
+                        state.surface_ready = viewport.is_some();
+                        app.frame_begin(&state.context());
+                        state.fixed_updates(&mut app);
+
+                        // one context for the rest of the frame, rather than one per callback.
+                        let ctx = state.context();
+                        app.update(&ctx, state.dt.as_secs_f64());
+                        apply_window_requests(&app, &mut state.shared.window_requests.lock());
+
+                        // a minimised window (0x0 on some window managers) or a collapsed viewport has
+                        // nothing to draw into, but the scene keeps updating and input keeps coming.
+                        let target = if is_zero_area(&app) {
+                            None
+                        } else {
+                            // the viewport is sized once per frame from the layout, however many resizes
+                            // came in since. The window's own surface belongs to Slint, which acquires
+                            // and reconfigures it, so there is no swapchain to recreate here.
+                            match &mut viewport {
+                                Some(target) => {
+                                    target.resize(&state.ctx, render_width, render_height);
+                                    Some(target)
+                                }
+                                None => state
+                                    .ctx
+                                    .create_render_target(render_width, render_height, state.viewport_format)
+                                    .inspect_err(|e| log::error!("Can't create the viewport: {e}"))
+                                    .ok()
+                                    .map(|target| viewport.insert(target)),
+                            }
+                        };
+                        if let Some(target) = &target {
+                            app.render(&ctx, target.view());
+                        }
+                        // encoders queued in update go out even on frames with nothing to draw.
+                        state.encoders.submit(&state.ctx);
+                        state.profiler.end_frame(&state.ctx);
+
+                        if let Some(viewport) = target {
+                            #[cfg(feature = "egui")]
+                            {
+                                let window = app.clone_strong();
+                                egui.borrow_mut().frame(window.window(), &state.ctx, viewport.view(), (width, height), state.viewport_size, |egui| {
+                                    app.ui(&ctx, egui)
+                                });
+                            }
+
+                            let compositor = compositor.get_or_insert_with(|| Compositor::new(&state.ctx, viewport, upscale_filter));
+                            if let Some(image) = compositor.composite(&state.ctx, viewport, (width, height)) {
+                                app.viewport_image(image);
+                            }
+                        }
+
+                        state.report_gpu_errors(&mut app);
+                        app.frame_end(&ctx);
+                        state.input_manager.reset_frame_deltas();
+                        state.ctx.end_cache_frame();
+                        frame_drawn.set(true);
+
+                        if state.is_exit_requested() {
+                            if !shutting_down {
+                                log::info!("Exit requested by the app");
+                                shutting_down = true;
+                                let _ = slint::quit_event_loop();
+                            }
+                            return;
+                        }
+                    }
+
+                    // on an interval, the timer below draws the frames instead.
+                    if redraw_mode == RedrawMode::Continuous && !matches!(poll_strategy, PollStrategy::Interval(_)) {
+                        match shared.limiter.remaining(last_frame) {
+                            Some(wait) => {
+                                let app = app.as_weak();
+                                slint::Timer::single_shot(wait, move || {
+                                    if let Some(app) = app.upgrade() {
+                                        app.window().request_redraw();
+                                    }
+                                });
+                            }
+                            None => app.window().request_redraw(),
+                        }
+                    }
+                }
+                slint::RenderingState::AfterRendering => {},
+                slint::RenderingState::RenderingTeardown => {
+                    if let Some(state) = renderer.as_ref() {
+                        app.exiting(&state.context());
+                        log::info!("Exiting app");
+                    }
+                    drop(renderer.take());
+                },
+                _ => {}
+            }
+        }).unwrap();
+
+        // the browser never hands control back from the event loop, and closing the tab doesn't
+        // tear the renderer down, so the page going away is the last chance to hear about it.
+        #[cfg(target_arch = "wasm32")]
+        {
+            use wasm_bindgen::{JsCast, closure::Closure};
+
+            let exit_renderer = exit_renderer.clone();
+            let app = slint_app.clone_strong();
+            let on_pagehide = Closure::<dyn FnMut(web_sys::PageTransitionEvent)>::new(move |event: web_sys::PageTransitionEvent| {
+                // a page kept in the back/forward cache may be shown again, so it isn't exiting.
+                if event.persisted() {
+                    return;
+                }
+                let Ok(mut renderer) = exit_renderer.try_borrow_mut() else {
+                    return;
+                };
+                if let Some(state) = renderer.take() {
+                    app.exiting(&state.context());
+                    log::info!("Exiting app");
+                }
+            });
+            if let Some(window) = web_sys::window() {
+                let _ = window.add_event_listener_with_callback("pagehide", on_pagehide.as_ref().unchecked_ref());
+            }
+            on_pagehide.forget();
+        }
+
+        slint_app.run()?;
+
+        // quitting the event loop doesn't necessarily tear the renderer down first, in which
+        // case the app still has to hear that it's exiting.
+        if let Some(state) = exit_renderer.borrow_mut().take() {
+            slint_app.clone_strong().exiting(&state.context());
+            log::info!("Exiting app");
+        }
+        Ok(())
+    }
+}
+
+/// The viewport's top-left corner and size in physical pixels, or `None` when it fills the
+/// window.
+fn viewport_bounds<S: ComponentHandle + AppBehaviour>(app: &S) -> Option<(PhysicalPosition<f64>, PhysicalSize<u32>)> {
+    app.viewport_rect()
+        .map(|rect| rect.to_physical(app.window().scale_factor()))
+}
+
+/// Hands the window whatever the scene asked for this frame, moving the IME cursor area from
+/// the viewport into window coordinates.
+fn apply_window_requests<S: ComponentHandle + AppBehaviour>(app: &S, requests: &mut WindowRequests) {
+    let ime_allowed = requests.ime_allowed.take();
+    let ime_cursor_area = requests.ime_cursor_area.take();
+    let cursor_icon = Some(requests.cursor_icon()).filter(|icon| requests.applied_cursor_icon != Some(*icon));
+    let fullscreen = requests.fullscreen.take();
+    let window_level = requests.window_level.take();
+    if ime_allowed.is_none() && ime_cursor_area.is_none() && cursor_icon.is_none() && fullscreen.is_none() && window_level.is_none() {
+        return;
+    }
+
+    let origin = viewport_bounds(app).map_or(PhysicalPosition::new(0.0, 0.0), |(origin, _)| origin);
+    let applied = app.window().with_winit_window(|window| {
+        if let Some(allowed) = ime_allowed {
+            window.set_ime_allowed(allowed);
+        }
+        if let Some((position, size)) = ime_cursor_area {
+            let position = PhysicalPosition::new(origin.x + position.x, origin.y + position.y);
+            window.set_ime_cursor_area(position, size);
+        }
+        if let Some(icon) = cursor_icon {
+            window.set_cursor(icon);
+        }
+        if let Some(fullscreen) = &fullscreen {
+            fullscreen.apply(window);
+        }
+        if let Some(level) = window_level {
+            set_window_level(window, level);
+        }
+    });
+    if applied.is_some() && cursor_icon.is_some() {
+        requests.applied_cursor_icon = cursor_icon;
+    }
+}
+
+/// Creates the instance and picks the adapter the window renders with, the way Slint would
+/// have: going by the `WGPU_*` environment variables `settings` were read from, and leaving out
+/// GL, which Slint's renderer draws wrongly on.
+///
+/// There is no window yet to ask for an adapter that can present to it, but on the backends
+/// that are left every adapter can present to the platform's own windows.
+#[cfg(not(target_arch = "wasm32"))]
+fn request_adapter(settings: &WGPUSettings) -> anyhow::Result<(wgpu::Instance, wgpu::Adapter)> {
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+        backends: settings.backends & !wgpu::Backends::GL,
+        flags: settings.instance_flags,
+        backend_options: settings.backend_options.clone(),
+        memory_budget_thresholds: settings.instance_memory_budget_thresholds,
+    });
+    let adapter = wgpu::util::initialize_adapter_from_env(&instance, None).or_else(|_| {
+        pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: settings.power_preference,
+            force_fallback_adapter: false,
+            compatible_surface: None,
+        }))
+    });
+    let adapter = adapter.map_err(|e| crate::no_adapter_error(e, false))?;
+    log::info!("Rendering with {} ({:?})", adapter.get_info().name, adapter.get_info().backend);
+    Ok((instance, adapter))
+}
+
+/// The parts of winit's event loop Slint lets us in on. Device events, which aren't tied to a
+/// window, go to the input manager and the scene, and [PollStrategy::Poll] keeps the loop from
+/// going to sleep. The app is only there once the component has been created after the backend.
+struct EventLoopHooks<S> {
+    renderer: Rc<RefCell<Option<State>>>,
+    app: Rc<RefCell<Option<S>>>,
+    poll: bool,
+}
+
+impl<S: AppBehaviour> CustomApplicationHandler for EventLoopHooks<S> {
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) -> EventResult {
+        // Slint resets the control flow to `Wait` at the start of every iteration and only
+        // shortens it for its timers afterwards, so this has to be set again every time.
+        if self.poll {
+            event_loop.set_control_flow(ControlFlow::Poll);
+        }
+        EventResult::Propagate
+    }
+
+    fn device_event(&mut self, _event_loop: &ActiveEventLoop, device_id: DeviceId, event: DeviceEvent) -> EventResult {
+        let (Ok(renderer), Ok(mut app)) = (self.renderer.try_borrow(), self.app.try_borrow_mut()) else {
+            return EventResult::Propagate;
+        };
+        if let (Some(state), Some(app)) = (renderer.as_ref(), app.as_mut()) {
+            state.input_manager.poll_device_event(&event);
+            app.device_event(&state.context(), device_id, &event);
+        }
+        EventResult::Propagate
+    }
+}
+
+/// The app's [AppBehaviour::render_scale], or 1 when it isn't a positive number.
+fn render_scale<S: AppBehaviour>(app: &S) -> f32 {
+    let scale = app.render_scale();
+    if scale.is_finite() && scale > 0.0 { scale } else { 1.0 }
+}
+
+/// The size scenes render at for a viewport of `(width, height)`, kept within what the device
+/// can allocate so a large scale on a large window doesn't fail texture creation.
+fn scale_size(graphics: &GraphicsContext, (width, height): (u32, u32), scale: f32) -> (u32, u32) {
+    let max = graphics.device.limits().max_texture_dimension_2d;
+    let scale = |size: u32| ((size as f32 * scale).round() as u32).clamp(1, max);
+    (scale(width), scale(height))
+}
+
+/// Whether the viewport has no area, such as when the window is minimised or the scene's panel
+/// is collapsed.
+fn is_zero_area<S: ComponentHandle + AppBehaviour>(app: &S) -> bool {
+    let size = app.window().size();
+    has_no_area(PhysicalSize::new(size.width, size.height), app.viewport_rect(), app.window().scale_factor())
+}
+
+/// Whether a scene laid out in `rect`, or filling a `window`-sized window without one, has
+/// nothing to draw into. The rect is checked as laid out, before [ViewportRect::to_physical]
+/// rounds it up to 1x1.
+fn has_no_area(window: PhysicalSize<u32>, rect: Option<ViewportRect>, scale_factor: f32) -> bool {
+    match rect {
+        Some(rect) => (rect.width * scale_factor).round() < 1.0 || (rect.height * scale_factor).round() < 1.0,
+        None => window.width == 0 || window.height == 0,
+    }
+}
+
+/// The size in physical pixels the scene is rendered at. A window outside its `limits` has
+/// already been asked to snap back, and until it does the scene is rendered within them.
+fn viewport_size<S: ComponentHandle + AppBehaviour>(app: &S, limits: &WindowConfig) -> (u32, u32) {
+    match viewport_bounds(app) {
+        Some((_, size)) => (size.width, size.height),
+        None => {
+            let size = app.window().size();
+            let size = limits.clamp_size(PhysicalSize::new(size.width, size.height), app.window().scale_factor() as f64);
+            (size.width.max(1), size.height.max(1))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use winit::dpi::PhysicalSize;
+
+    use crate::scene::ViewportRect;
+
+    use super::has_no_area;
+
+    #[test]
+    fn minimised_window_stops_and_restores_rendering() {
+        let frames = [PhysicalSize::new(800, 600), PhysicalSize::new(0, 0), PhysicalSize::new(800, 0), PhysicalSize::new(800, 600)];
+        let skipped: Vec<_> = frames.into_iter().map(|size| has_no_area(size, None, 1.0)).collect();
+        assert_eq!(skipped, [false, true, true, false]);
+    }
+
+    #[test]
+    fn collapsed_panel_has_no_area() {
+        let window = PhysicalSize::new(800, 600);
+        let panels = [
+            ViewportRect::new(10.0, 10.0, 400.0, 300.0),
+            ViewportRect::new(10.0, 10.0, 0.0, 300.0),
+            ViewportRect::new(10.0, 10.0, 400.0, 0.2),
+            ViewportRect::new(10.0, 10.0, 400.0, 300.0),
+        ];
+        let skipped: Vec<_> = panels.into_iter().map(|rect| has_no_area(window, Some(rect), 2.0)).collect();
+        assert_eq!(skipped, [false, true, true, false]);
+
+        // the size the panel renders at is still never zero.
+        let (_, size) = panels[1].to_physical(2.0);
+        assert_eq!(size, PhysicalSize::new(1, 600));
+    }
+}
@@ -0,0 +1,132 @@
+use std::{
+    sync::{Arc, mpsc},
+    thread::JoinHandle,
+};
+
+use crate::{GraphicsContext, texture::Texture};
+#[cfg(feature = "image")]
+use crate::mipmap::MipLevels;
+
+type Job = Box<dyn FnOnce(&GraphicsContext) + Send>;
+
+/// Creates GPU resources on a background thread, so decoding and uploading big assets doesn't
+/// hitch the render loop.
+///
+/// Resource creation and `Queue::write_*` are thread-safe, which is all the worker relies on.
+/// Written data is staged by wgpu and lands on the GPU ahead of the next `queue.submit` from any
+/// thread, so an asset is safe to use from the frame it is picked up in.
+///
+/// Jobs run one at a time in the order they were queued. Dropping the uploader finishes the
+/// queued jobs before returning.
+pub struct AssetUploader {
+    jobs: Option<mpsc::Sender<Job>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl AssetUploader {
+    pub fn new(graphics: Arc<GraphicsContext>) -> Self {
+        let (jobs, queue) = mpsc::channel::<Job>();
+        let worker = std::thread::Builder::new()
+            .name("lyrebird asset uploader".into())
+            .spawn(move || {
+                for job in queue {
+                    job(&graphics);
+                }
+            })
+            .expect("failed to spawn the asset upload thread");
+
+        Self {
+            jobs: Some(jobs),
+            worker: Some(worker),
+        }
+    }
+
+    /// Runs `job` on the upload thread, handing back whatever it creates.
+    pub fn spawn<T, F>(&self, job: F) -> PendingAsset<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&GraphicsContext) -> anyhow::Result<T> + Send + 'static,
+    {
+        let (result, receiver) = mpsc::sync_channel(1);
+        let job: Job = Box::new(move |graphics| {
+            // the caller may have given up on the asset already, which is fine.
+            let _ = result.send(job(graphics));
+        });
+
+        if let Some(jobs) = &self.jobs {
+            let _ = jobs.send(job);
+        }
+        PendingAsset { receiver }
+    }
+
+    /// Uploads tightly packed RGBA8 pixels into a new sRGB texture that can be sampled.
+    pub fn upload_texture(&self, width: u32, height: u32, rgba: Vec<u8>) -> PendingAsset<Texture> {
+        self.spawn(move |graphics| Texture::from_rgba8(graphics, width, height, &rgba, "uploaded texture"))
+    }
+
+    /// Uploads `contents` into a new buffer. `COPY_DST` is added to `usage` for the upload.
+    pub fn upload_buffer(&self, usage: wgpu::BufferUsages, contents: Vec<u8>) -> PendingAsset<wgpu::Buffer> {
+        self.spawn(move |graphics| {
+            let buffer = graphics.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("uploaded buffer"),
+                size: contents.len() as u64,
+                usage: usage | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            graphics.queue.write_buffer(&buffer, 0, &contents);
+            Ok(buffer)
+        })
+    }
+
+    /// Decodes an image file (PNG or JPEG) and uploads it as a texture, both off the calling
+    /// thread.
+    #[cfg(feature = "image")]
+    pub fn load_texture(&self, path: impl Into<std::path::PathBuf>) -> PendingAsset<Texture> {
+        let path = path.into();
+        self.spawn(move |graphics| Texture::from_path(graphics, &path))
+    }
+
+    /// Like [Self::load_texture], also generating `levels` mip levels on the upload thread, see
+    /// [Texture::with_mipmaps].
+    #[cfg(feature = "image")]
+    pub fn load_texture_with_mipmaps(&self, path: impl Into<std::path::PathBuf>, levels: MipLevels) -> PendingAsset<Texture> {
+        let path = path.into();
+        self.spawn(move |graphics| Texture::from_path(graphics, &path)?.with_mipmaps(graphics, levels))
+    }
+}
+
+impl Drop for AssetUploader {
+    fn drop(&mut self) {
+        // closing the channel lets the worker drain what's left and stop.
+        drop(self.jobs.take());
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// An asset that is still being created by an [AssetUploader].
+pub struct PendingAsset<T> {
+    receiver: mpsc::Receiver<anyhow::Result<T>>,
+}
+
+impl<T> PendingAsset<T> {
+    /// Returns the asset if it is ready, without blocking. This should be polled once per frame
+    /// until it yields something, after which it returns an error.
+    pub fn try_take(&self) -> Option<anyhow::Result<T>> {
+        match self.receiver.try_recv() {
+            Ok(result) => Some(result),
+            Err(mpsc::TryRecvError::Empty) => None,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                Some(Err(anyhow::anyhow!("asset was already taken or its upload was abandoned")))
+            }
+        }
+    }
+
+    /// Blocks until the asset is ready.
+    pub fn wait(self) -> anyhow::Result<T> {
+        self.receiver
+            .recv()
+            .map_err(|_| anyhow::anyhow!("asset upload was abandoned"))?
+    }
+}
@@ -0,0 +1,168 @@
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+};
+
+use crate::GraphicsContext;
+
+/// How many frames a cached pipeline or bind group may go unused before it is evicted.
+const MAX_UNUSED_FRAMES: u64 = 300;
+
+struct Entry<T> {
+    value: T,
+    last_used: u64,
+}
+
+/// GPU objects keyed by a hash of the descriptor they were made from, evicting the ones that
+/// haven't been asked for in a while.
+pub(crate) struct ResourceCache<T> {
+    entries: HashMap<u64, Entry<T>>,
+    frame: u64,
+}
+
+impl<T> Default for ResourceCache<T> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+            frame: 0,
+        }
+    }
+}
+
+impl<T: Clone> ResourceCache<T> {
+    fn get_or_insert_with(&mut self, key: u64, create: impl FnOnce() -> T) -> T {
+        let frame = self.frame;
+        let entry = self.entries.entry(key).or_insert_with(|| Entry {
+            value: create(),
+            last_used: frame,
+        });
+        entry.last_used = frame;
+        entry.value.clone()
+    }
+
+    /// Moves on to the next frame, dropping everything that went unused for too long.
+    fn next_frame(&mut self) {
+        self.frame += 1;
+        let frame = self.frame;
+        self.entries.retain(|_, entry| frame - entry.last_used <= MAX_UNUSED_FRAMES);
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+pub(crate) type PipelineCache = ResourceCache<wgpu::RenderPipeline>;
+pub(crate) type BindGroupCache = ResourceCache<wgpu::BindGroup>;
+
+fn hash_compilation_options(options: &wgpu::PipelineCompilationOptions, state: &mut impl Hasher) {
+    for (name, value) in options.constants {
+        name.hash(state);
+        value.to_bits().hash(state);
+    }
+    options.zero_initialize_workgroup_memory.hash(state);
+}
+
+/// Hashes everything about `desc` that affects the pipeline, which is all of it but the label.
+/// Layouts and shader modules are told apart by identity rather than contents.
+fn hash_render_pipeline(desc: &wgpu::RenderPipelineDescriptor) -> u64 {
+    let mut state = DefaultHasher::new();
+    desc.layout.hash(&mut state);
+
+    desc.vertex.module.hash(&mut state);
+    desc.vertex.entry_point.hash(&mut state);
+    hash_compilation_options(&desc.vertex.compilation_options, &mut state);
+    desc.vertex.buffers.hash(&mut state);
+
+    desc.primitive.hash(&mut state);
+    desc.depth_stencil.hash(&mut state);
+    desc.multisample.hash(&mut state);
+
+    if let Some(fragment) = &desc.fragment {
+        fragment.module.hash(&mut state);
+        fragment.entry_point.hash(&mut state);
+        hash_compilation_options(&fragment.compilation_options, &mut state);
+        fragment.targets.hash(&mut state);
+    }
+
+    desc.multiview.hash(&mut state);
+    desc.cache.hash(&mut state);
+    state.finish()
+}
+
+fn hash_buffer_binding(binding: &wgpu::BufferBinding, state: &mut impl Hasher) {
+    binding.buffer.hash(state);
+    binding.offset.hash(state);
+    binding.size.hash(state);
+}
+
+/// Hashes the layout and resources of `desc`, or returns `None` for resources that can't be
+/// hashed.
+fn hash_bind_group(desc: &wgpu::BindGroupDescriptor) -> Option<u64> {
+    let mut state = DefaultHasher::new();
+    desc.layout.hash(&mut state);
+
+    for entry in desc.entries {
+        entry.binding.hash(&mut state);
+        std::mem::discriminant(&entry.resource).hash(&mut state);
+        match &entry.resource {
+            wgpu::BindingResource::Buffer(binding) => hash_buffer_binding(binding, &mut state),
+            wgpu::BindingResource::BufferArray(bindings) => {
+                for binding in *bindings {
+                    hash_buffer_binding(binding, &mut state);
+                }
+            }
+            wgpu::BindingResource::Sampler(sampler) => sampler.hash(&mut state),
+            wgpu::BindingResource::SamplerArray(samplers) => samplers.hash(&mut state),
+            wgpu::BindingResource::TextureView(view) => view.hash(&mut state),
+            wgpu::BindingResource::TextureViewArray(views) => views.hash(&mut state),
+            _ => return None,
+        }
+    }
+    Some(state.finish())
+}
+
+impl GraphicsContext {
+    /// Returns the render pipeline `desc` describes, creating it the first time it's asked for,
+    /// so scenes can build their pipelines every frame without the driver compiling them again.
+    ///
+    /// Pipelines are looked up by a hash of the whole descriptor except its label, with
+    /// layouts and shader modules compared by identity. A pipeline that goes unused for a few
+    /// hundred frames is evicted, and the cache is cleared whenever
+    /// [crate::prelude::ShaderManager] reloads a shader.
+    pub fn cached_render_pipeline(&self, desc: &wgpu::RenderPipelineDescriptor) -> wgpu::RenderPipeline {
+        let key = hash_render_pipeline(desc);
+        self.pipelines
+            .lock()
+            .get_or_insert_with(key, || self.device.create_render_pipeline(desc))
+    }
+
+    /// Like [Self::cached_render_pipeline], for bind groups. Bind groups are matched on their
+    /// layout and the exact buffers, ranges, samplers and views they bind. Resources that
+    /// can't be compared, such as acceleration structures, get a new bind group every time.
+    pub fn cached_bind_group(&self, desc: &wgpu::BindGroupDescriptor) -> wgpu::BindGroup {
+        match hash_bind_group(desc) {
+            Some(key) => self
+                .bind_groups
+                .lock()
+                .get_or_insert_with(key, || self.device.create_bind_group(desc)),
+            None => self.device.create_bind_group(desc),
+        }
+    }
+
+    /// Drops every cached pipeline, so the next request for each builds it again.
+    pub fn clear_pipeline_cache(&self) {
+        self.pipelines.lock().clear();
+    }
+
+    /// Drops every cached bind group.
+    pub fn clear_bind_group_cache(&self) {
+        self.bind_groups.lock().clear();
+    }
+
+    /// Ages the caches by a frame. Called by the app loop once each frame is done.
+    pub(crate) fn end_cache_frame(&self) {
+        self.pipelines.lock().next_frame();
+        self.bind_groups.lock().next_frame();
+    }
+}
@@ -0,0 +1,236 @@
+use glam::{Mat4, Vec2, Vec3};
+use winit::{event::MouseButton, keyboard::KeyCode};
+
+use crate::{GraphicsContext, input::InputManager};
+
+/// How a [Camera] projects the scene.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    Perspective {
+        /// Vertical field of view in radians.
+        fov_y: f32,
+        near: f32,
+        far: f32,
+    },
+    Orthographic {
+        /// How many world units fit vertically in the view; the width follows the aspect ratio.
+        height: f32,
+        near: f32,
+        far: f32,
+    },
+}
+
+/// A camera looking from `position` at `target`, right handed with y up and depth going from 0
+/// to 1 as wgpu expects.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    pub position: Vec3,
+    pub target: Vec3,
+    pub up: Vec3,
+    pub projection: Projection,
+    /// Width over height of the view.
+    pub aspect: f32,
+}
+
+impl Camera {
+    /// A perspective camera with a vertical field of view of `fov_y` radians.
+    pub fn perspective(position: Vec3, target: Vec3, fov_y: f32, aspect: f32) -> Self {
+        Self {
+            position,
+            target,
+            up: Vec3::Y,
+            projection: Projection::Perspective {
+                fov_y,
+                near: 0.1,
+                far: 1000.0,
+            },
+            aspect,
+        }
+    }
+
+    /// An orthographic camera that fits `height` world units vertically.
+    pub fn orthographic(position: Vec3, target: Vec3, height: f32, aspect: f32) -> Self {
+        Self {
+            position,
+            target,
+            up: Vec3::Y,
+            projection: Projection::Orthographic {
+                height,
+                near: 0.1,
+                far: 1000.0,
+            },
+            aspect,
+        }
+    }
+
+    /// Updates the aspect ratio for a view of `width`x`height` pixels, such as after a resize.
+    /// Zero sizes (minimised windows) are ignored.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if width > 0 && height > 0 {
+            self.aspect = width as f32 / height as f32;
+        }
+    }
+
+    pub fn view(&self) -> Mat4 {
+        Mat4::look_at_rh(self.position, self.target, self.up)
+    }
+
+    pub fn projection(&self) -> Mat4 {
+        match self.projection {
+            Projection::Perspective { fov_y, near, far } => Mat4::perspective_rh(fov_y, self.aspect, near, far),
+            Projection::Orthographic { height, near, far } => {
+                let half_height = height / 2.0;
+                let half_width = half_height * self.aspect;
+                Mat4::orthographic_rh(-half_width, half_width, -half_height, half_height, near, far)
+            }
+        }
+    }
+
+    pub fn view_proj(&self) -> Mat4 {
+        self.projection() * self.view()
+    }
+
+    /// Creates a uniform buffer big enough for [Self::write_uniform].
+    pub fn create_uniform_buffer(&self, graphics: &GraphicsContext) -> wgpu::Buffer {
+        graphics.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("camera uniform"),
+            size: size_of::<Mat4>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// Writes [Self::view_proj] into `buffer` as a column-major `mat4x4<f32>`.
+    pub fn write_uniform(&self, graphics: &GraphicsContext, buffer: &wgpu::Buffer) {
+        graphics.queue.write_buffer(buffer, 0, bytemuck::bytes_of(&self.view_proj()));
+    }
+}
+
+/// How far the cursor moved since the last frame, while `button` is held.
+fn cursor_motion(input: &InputManager, button: MouseButton, last: &mut Option<Vec2>) -> Vec2 {
+    let position = input
+        .cursor_position()
+        .filter(|_| input.is_mouse_down(button))
+        .map(|position| Vec2::new(position.x as f32, position.y as f32));
+
+    let motion = match (*last, position) {
+        (Some(from), Some(to)) => to - from,
+        _ => Vec2::ZERO,
+    };
+    *last = position;
+    motion
+}
+
+/// Orbits a [Camera] around its target: drag with the left mouse button to rotate and scroll
+/// to zoom.
+#[derive(Debug, Clone)]
+pub struct OrbitController {
+    pub distance: f32,
+    /// Rotation around the up axis, in radians.
+    pub yaw: f32,
+    /// Rotation above the horizon, in radians.
+    pub pitch: f32,
+    /// Radians per pixel dragged.
+    pub sensitivity: f32,
+    /// Fraction of the distance moved per pixel scrolled.
+    pub zoom_speed: f32,
+    last_cursor: Option<Vec2>,
+}
+
+impl OrbitController {
+    /// Starts from wherever `camera` currently is relative to its target.
+    pub fn new(camera: &Camera) -> Self {
+        let offset = camera.position - camera.target;
+        let distance = offset.length().max(0.01);
+        Self {
+            distance,
+            yaw: offset.x.atan2(offset.z),
+            pitch: (offset.y / distance).asin(),
+            sensitivity: 0.005,
+            zoom_speed: 0.002,
+            last_cursor: None,
+        }
+    }
+
+    pub fn update(&mut self, camera: &mut Camera, input: &InputManager) {
+        let motion = cursor_motion(input, MouseButton::Left, &mut self.last_cursor);
+        self.yaw -= motion.x * self.sensitivity;
+        self.pitch = (self.pitch + motion.y * self.sensitivity)
+            .clamp(-std::f32::consts::FRAC_PI_2 + 0.01, std::f32::consts::FRAC_PI_2 - 0.01);
+
+        let (_, scroll) = input.scroll_delta();
+        self.distance = (self.distance * (1.0 - scroll * self.zoom_speed)).max(0.01);
+
+        let offset = Vec3::new(
+            self.pitch.cos() * self.yaw.sin(),
+            self.pitch.sin(),
+            self.pitch.cos() * self.yaw.cos(),
+        ) * self.distance;
+        camera.position = camera.target + offset;
+    }
+}
+
+/// Flies a [Camera] around freely: WASD to move, Space and Shift to go up and down, and drag
+/// with the right mouse button to look around.
+#[derive(Debug, Clone)]
+pub struct FlyController {
+    /// World units per second.
+    pub speed: f32,
+    /// Radians per pixel dragged.
+    pub sensitivity: f32,
+    pub yaw: f32,
+    pub pitch: f32,
+    last_cursor: Option<Vec2>,
+}
+
+impl FlyController {
+    /// Starts facing wherever `camera` currently looks.
+    pub fn new(camera: &Camera) -> Self {
+        let forward = (camera.target - camera.position).normalize_or(Vec3::NEG_Z);
+        Self {
+            speed: 5.0,
+            sensitivity: 0.003,
+            yaw: forward.x.atan2(-forward.z),
+            pitch: forward.y.asin(),
+            last_cursor: None,
+        }
+    }
+
+    pub fn update(&mut self, camera: &mut Camera, input: &InputManager, dt: f64) {
+        let motion = cursor_motion(input, MouseButton::Right, &mut self.last_cursor);
+        self.yaw += motion.x * self.sensitivity;
+        self.pitch = (self.pitch - motion.y * self.sensitivity)
+            .clamp(-std::f32::consts::FRAC_PI_2 + 0.01, std::f32::consts::FRAC_PI_2 - 0.01);
+
+        let forward = Vec3::new(
+            self.pitch.cos() * self.yaw.sin(),
+            self.pitch.sin(),
+            -self.pitch.cos() * self.yaw.cos(),
+        );
+        let right = forward.cross(camera.up).normalize_or_zero();
+
+        let mut direction = Vec3::ZERO;
+        let held = |key| input.is_key_down(key);
+        if held(KeyCode::KeyW) {
+            direction += forward;
+        }
+        if held(KeyCode::KeyS) {
+            direction -= forward;
+        }
+        if held(KeyCode::KeyD) {
+            direction += right;
+        }
+        if held(KeyCode::KeyA) {
+            direction -= right;
+        }
+        if held(KeyCode::Space) {
+            direction += camera.up;
+        }
+        if held(KeyCode::ShiftLeft) {
+            direction -= camera.up;
+        }
+
+        camera.position += direction.normalize_or_zero() * self.speed * dt as f32;
+        camera.target = camera.position + forward;
+    }
+}
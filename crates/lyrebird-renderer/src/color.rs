@@ -0,0 +1,112 @@
+/// A colour with straight (not premultiplied) alpha, stored as linear RGB.
+///
+/// Scenes render into a linear [crate::State::FORMAT] viewport (unless
+/// [crate::AppBuilder::viewport_format] says otherwise) that is sRGB-encoded when it is
+/// composited into the window, so clear colours and shader colours should be linear. Colours
+/// picked in an image editor or written as CSS-style hex are sRGB, which is what
+/// [Self::from_hex], [Self::rgba8] and [Self::srgb] take, converting them to linear. Passing
+/// sRGB values straight to wgpu makes everything look washed out.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+/// The sRGB transfer function, from an encoded component to linear light.
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+impl Color {
+    pub const TRANSPARENT: Self = Self::linear(0.0, 0.0, 0.0, 0.0);
+    pub const BLACK: Self = Self::linear(0.0, 0.0, 0.0, 1.0);
+    pub const WHITE: Self = Self::linear(1.0, 1.0, 1.0, 1.0);
+    pub const RED: Self = Self::linear(1.0, 0.0, 0.0, 1.0);
+    pub const GREEN: Self = Self::linear(0.0, 1.0, 0.0, 1.0);
+    pub const BLUE: Self = Self::linear(0.0, 0.0, 1.0, 1.0);
+
+    /// A colour from components that are already linear.
+    pub const fn linear(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// A colour from sRGB-encoded components in `0.0..=1.0`. Alpha is linear either way.
+    pub fn srgb(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self::linear(srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b), a)
+    }
+
+    /// A colour from sRGB-encoded bytes, as colour pickers show them.
+    pub fn rgba8(r: u8, g: u8, b: u8, a: u8) -> Self {
+        let unorm = |c: u8| c as f32 / 255.0;
+        Self::srgb(unorm(r), unorm(g), unorm(b), unorm(a))
+    }
+
+    /// An opaque colour from sRGB hex, such as `0x1e90ff`.
+    pub fn from_hex(rgb: u32) -> Self {
+        let [_, r, g, b] = rgb.to_be_bytes();
+        Self::rgba8(r, g, b, 255)
+    }
+
+    /// Like [Self::from_hex], with alpha in the lowest byte: `0x1e90ff80`.
+    pub fn from_hex_rgba(rgba: u32) -> Self {
+        let [r, g, b, a] = rgba.to_be_bytes();
+        Self::rgba8(r, g, b, a)
+    }
+
+    /// The same colour with alpha replaced.
+    pub fn with_alpha(self, a: f32) -> Self {
+        Self { a, ..self }
+    }
+
+    /// The sRGB-encoded components, for handing to something that expects sRGB, such as Slint.
+    pub fn to_srgb(self) -> [f32; 4] {
+        [linear_to_srgb(self.r), linear_to_srgb(self.g), linear_to_srgb(self.b), self.a]
+    }
+
+    /// The sRGB-encoded components as bytes, clamped to `0..=255`.
+    pub fn to_rgba8(self) -> [u8; 4] {
+        self.to_srgb().map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8)
+    }
+
+    /// The linear components, as [crate::prelude::DebugDraw] and the 2D sprites take them.
+    pub fn to_array(self) -> [f32; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
+}
+
+impl From<Color> for wgpu::Color {
+    fn from(color: Color) -> Self {
+        Self {
+            r: color.r as f64,
+            g: color.g as f64,
+            b: color.b as f64,
+            a: color.a as f64,
+        }
+    }
+}
+
+impl From<wgpu::Color> for Color {
+    fn from(color: wgpu::Color) -> Self {
+        Self::linear(color.r as f32, color.g as f32, color.b as f32, color.a as f32)
+    }
+}
+
+impl From<Color> for [f32; 4] {
+    fn from(color: Color) -> Self {
+        color.to_array()
+    }
+}
@@ -0,0 +1,189 @@
+use crate::{GraphicsContext, State, pass::RenderPassBuilder, target::RenderTarget};
+
+/// The format of the view scenes render into, picked with [crate::AppBuilder::viewport_format].
+///
+/// The window's own surface is created and configured by Slint, which always picks an 8-bit
+/// format, so this is about the viewport lyrebird composites into it instead. How the
+/// viewport is shown follows from its format: float and `*Srgb` formats hold linear colour
+/// and are sRGB-encoded on the way to the window, while other formats are shown exactly as
+/// they are, for pipelines that apply their own gamma.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ViewportFormat {
+    /// Linear `Rgba16Float`, sRGB-encoded for display. Colours go in linear, see
+    /// [crate::prelude::Color].
+    #[default]
+    PreferSrgb,
+    /// `Rgba8Unorm`, displayed as written with no encoding, for scenes that gamma-encode
+    /// themselves.
+    PreferLinear,
+    /// Exactly `format`, which has to be renderable and filterable on the device. When it isn't,
+    /// a warning is logged and [Self::PreferSrgb]'s format is used instead.
+    Explicit(wgpu::TextureFormat),
+}
+
+impl ViewportFormat {
+    /// The format to create the viewport with on `graphics`' device.
+    pub(crate) fn resolve(self, graphics: &GraphicsContext) -> wgpu::TextureFormat {
+        let format = match self {
+            Self::PreferSrgb => State::FORMAT,
+            Self::PreferLinear => wgpu::TextureFormat::Rgba8Unorm,
+            Self::Explicit(format) => format,
+        };
+
+        let device_features = graphics.device.features();
+        let features = format.guaranteed_format_features(device_features);
+        let usable = features
+            .allowed_usages
+            .contains(wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING)
+            && features.flags.contains(wgpu::TextureFormatFeatureFlags::FILTERABLE)
+            && format.sample_type(None, Some(device_features)) == Some(wgpu::TextureSampleType::Float { filterable: true });
+        if usable {
+            return format;
+        }
+
+        log::warn!("The viewport can't be {format:?}, as it isn't renderable and filterable here. Using {:?}", State::FORMAT);
+        State::FORMAT
+    }
+}
+
+/// Whether a viewport in `format` holds linear colour, which has to be sRGB-encoded for display.
+fn is_linear(format: wgpu::TextureFormat) -> bool {
+    use wgpu::TextureFormat::*;
+
+    format.is_srgb() || matches!(format, R16Float | Rg16Float | Rgba16Float | R32Float | Rg32Float | Rgba32Float | Rg11b10Ufloat)
+}
+
+/// Gets the scene in front of the user by blitting the viewport into an `Rgba8Unorm` target,
+/// as Slint can only display 8-bit RGBA textures. See [ViewportFormat] for when the viewport
+/// is sRGB-encoded on the way.
+///
+/// The result is handed to the app through [crate::prelude::AppBehaviour::viewport_image] and
+/// shown wherever the component puts it. Slint draws its elements in declaration order, so the
+/// scene sits under everything declared after its `Image`.
+///
+/// The display target is always the size of the viewport on screen, so a scene rendered at a
+/// [crate::prelude::AppBehaviour::render_scale] below 1 is stretched to fit, filtered with the
+/// sampler picked by [crate::AppBuilder::upscale_filter].
+pub(crate) struct Compositor {
+    pipeline: wgpu::RenderPipeline,
+    sampler: wgpu::Sampler,
+    display: Option<RenderTarget>,
+    /// The scene view the bind group reads, rebuilt only when the scene target is recreated so
+    /// a resize doesn't leave old viewport textures alive in the bind group cache.
+    bind_group: Option<(wgpu::TextureView, wgpu::BindGroup)>,
+}
+
+impl Compositor {
+    pub(crate) const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+
+    /// Creates a compositor for scenes rendered into targets like `scene`, scaled with `filter`.
+    pub(crate) fn new(graphics: &GraphicsContext, scene: &RenderTarget, filter: wgpu::FilterMode) -> Self {
+        let device = &graphics.device;
+        let constants = [("ENCODE_SRGB", if is_linear(scene.format()) { 1.0 } else { 0.0 })];
+        let shader = device.create_shader_module(wgpu::include_wgsl!("composite.wgsl"));
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("composite pipeline layout"),
+            bind_group_layouts: &[scene.bind_group_layout()],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("composite pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                compilation_options: Default::default(),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                compilation_options: wgpu::PipelineCompilationOptions {
+                    constants: &constants,
+                    ..Default::default()
+                },
+                targets: &[Some(Self::FORMAT.into())],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("composite sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: filter,
+            min_filter: filter,
+            ..Default::default()
+        });
+
+        Self { pipeline, sampler, display: None, bind_group: None }
+    }
+
+    /// Copies `scene` into a display target of `(width, height)`, returning a new image for
+    /// Slint when the target had to be (re)created, such as on the first frame or after a resize.
+    pub(crate) fn composite(
+        &mut self,
+        graphics: &GraphicsContext,
+        scene: &RenderTarget,
+        (width, height): (u32, u32),
+    ) -> Option<slint::Image> {
+        let changed = match &mut self.display {
+            Some(display) => display.resize(graphics, width, height),
+            None => {
+                let display = graphics
+                    .create_render_target(width, height, Self::FORMAT)
+                    .inspect_err(|e| log::error!("Can't create the display target: {e}"))
+                    .ok()?;
+                self.display = Some(display);
+                true
+            }
+        };
+        let display = self.display.as_ref()?;
+        if self.bind_group.as_ref().is_none_or(|(view, _)| view != scene.view()) {
+            let bind_group = graphics.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("composite bind group"),
+                layout: scene.bind_group_layout(),
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(scene.view()),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&self.sampler),
+                    },
+                ],
+            });
+            self.bind_group = Some((scene.view().clone(), bind_group));
+        }
+        let (_, bind_group) = self.bind_group.as_ref()?;
+
+        let mut encoder = graphics.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Composite Encoder"),
+        });
+        {
+            let mut pass = RenderPassBuilder::for_target(display)
+                .label("Composite Pass")
+                .begin(&mut encoder);
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, bind_group, &[]);
+            pass.draw(0..3, 0..1);
+        }
+        graphics.submit(encoder);
+
+        if !changed {
+            return None;
+        }
+        display
+            .to_slint_image()
+            .inspect_err(|e| log::error!("Unable to hand the viewport to Slint: {e}"))
+            .ok()
+    }
+}
@@ -0,0 +1,91 @@
+use std::sync::Arc;
+
+use crate::GraphicsContext;
+
+/// A GPU device for compute work alone, with no window, surface or scene to drive.
+///
+/// The device is set up the same way as for [crate::run_headless], falling back to a software
+/// adapter when there is no GPU. Everything in [GraphicsContext] is there through
+/// [Self::graphics], such as the pipeline cache and staged uploads, and [Self::dispatch] and
+/// [Self::read_buffer] cover the usual "run a shader, read back the results" loop.
+pub struct ComputeContext {
+    graphics: Arc<GraphicsContext>,
+}
+
+impl ComputeContext {
+    pub fn new() -> anyhow::Result<Self> {
+        let instance = wgpu::Instance::default();
+        let graphics = crate::headless::create_graphics(&instance, "lyrebird compute device")?;
+        Ok(Self {
+            graphics: Arc::new(graphics),
+        })
+    }
+
+    pub fn graphics(&self) -> &Arc<GraphicsContext> {
+        &self.graphics
+    }
+
+    pub fn device(&self) -> &Arc<wgpu::Device> {
+        &self.graphics.device
+    }
+
+    pub fn queue(&self) -> &Arc<wgpu::Queue> {
+        &self.graphics.queue
+    }
+
+    /// Runs `pipeline` over `workgroups` (x, y, z) with `bind_groups` bound from group 0 up,
+    /// and submits it. It doesn't wait for the GPU to finish; [Self::read_buffer] does.
+    pub fn dispatch(&self, pipeline: &wgpu::ComputePipeline, bind_groups: &[&wgpu::BindGroup], workgroups: (u32, u32, u32)) {
+        let mut encoder = self.device().create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("compute dispatch encoder"),
+        });
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("compute dispatch"),
+                timestamp_writes: None,
+            });
+            pass.set_pipeline(pipeline);
+            for (index, bind_group) in bind_groups.iter().enumerate() {
+                pass.set_bind_group(index as u32, *bind_group, &[]);
+            }
+            let (x, y, z) = workgroups;
+            pass.dispatch_workgroups(x, y, z);
+        }
+        self.graphics.submit(encoder);
+    }
+
+    /// Waits for the work submitted so far and returns the contents of `buffer`, which needs
+    /// `COPY_SRC` usage. Storage buffers can't be mapped directly, so it goes through a
+    /// temporary buffer.
+    pub fn read_buffer(&self, buffer: &wgpu::Buffer) -> anyhow::Result<Vec<u8>> {
+        let readback = self.device().create_buffer(&wgpu::BufferDescriptor {
+            label: Some("compute readback"),
+            size: buffer.size(),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self.device().create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("compute readback encoder"),
+        });
+        encoder.copy_buffer_to_buffer(buffer, 0, &readback, 0, buffer.size());
+        self.graphics.submit(encoder);
+
+        let slice = readback.slice(..);
+        let (tx, rx) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = tx.send(result);
+        });
+        self.device().poll(wgpu::PollType::wait_indefinitely())?;
+        rx.recv()??;
+
+        let bytes = slice.get_mapped_range().to_vec();
+        readback.unmap();
+        Ok(bytes)
+    }
+
+    /// [Self::read_buffer], reinterpreted as a slice of `T`.
+    pub fn read_buffer_as<T: bytemuck::Pod>(&self, buffer: &wgpu::Buffer) -> anyhow::Result<Vec<T>> {
+        Ok(bytemuck::pod_collect_to_vec(&self.read_buffer(buffer)?))
+    }
+}
@@ -0,0 +1,224 @@
+use glam::{Mat4, Vec3};
+
+use crate::{GraphicsContext, pass::RenderPassBuilder, scene::Context};
+
+/// Segments used to approximate a [DebugDraw::circle].
+const CIRCLE_SEGMENTS: usize = 32;
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct DebugVertex {
+    position: [f32; 3],
+    color: [f32; 4],
+}
+
+impl DebugVertex {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 2] = wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x4];
+
+    fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: size_of::<Self>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+/// Throwaway line drawing for debugging spatial code: rays, bounding boxes, gizmos.
+///
+/// Shapes are collected over the frame and drawn in one line-list draw by [Self::flush], which
+/// then forgets them, so anything that should stay on screen has to be added again every frame.
+/// Lines are drawn on top of everything, without depth testing. Colours are linear RGBA.
+///
+/// Uploads go through the staging belt, so the encoder passed to [Self::flush] has to be
+/// submitted with [GraphicsContext::submit].
+pub struct DebugDraw {
+    pipeline: wgpu::RenderPipeline,
+    camera: wgpu::Buffer,
+    camera_bind_group: wgpu::BindGroup,
+    vertex_buffer: wgpu::Buffer,
+    capacity: usize,
+    vertices: Vec<DebugVertex>,
+}
+
+impl DebugDraw {
+    const INITIAL_CAPACITY: usize = 1024;
+
+    /// Creates a debug drawer that draws into views of the viewport's format.
+    pub fn new(ctx: &Context) -> Self {
+        Self::with_format(&ctx.graphics, ctx.viewport_format())
+    }
+
+    /// Creates a debug drawer that draws into views of `format`.
+    pub fn with_format(graphics: &GraphicsContext, format: wgpu::TextureFormat) -> Self {
+        let device = &graphics.device;
+        let shader = device.create_shader_module(wgpu::include_wgsl!("debug_draw.wgsl"));
+
+        let camera_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("debug draw camera bind group layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("debug draw pipeline layout"),
+            bind_group_layouts: &[&camera_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("debug draw pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                compilation_options: Default::default(),
+                buffers: &[DebugVertex::layout()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                compilation_options: Default::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::LineList,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let camera = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("debug draw camera"),
+            size: size_of::<Mat4>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("debug draw camera bind group"),
+            layout: &camera_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: camera.as_entire_binding(),
+            }],
+        });
+
+        Self {
+            pipeline,
+            camera,
+            camera_bind_group,
+            vertex_buffer: Self::create_vertex_buffer(graphics, Self::INITIAL_CAPACITY),
+            capacity: Self::INITIAL_CAPACITY,
+            vertices: Vec::with_capacity(Self::INITIAL_CAPACITY),
+        }
+    }
+
+    fn create_vertex_buffer(graphics: &GraphicsContext, capacity: usize) -> wgpu::Buffer {
+        graphics.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("debug draw vertices"),
+            size: (capacity * size_of::<DebugVertex>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    pub fn line(&mut self, a: Vec3, b: Vec3, color: [f32; 4]) {
+        self.vertices.push(DebugVertex {
+            position: a.into(),
+            color,
+        });
+        self.vertices.push(DebugVertex {
+            position: b.into(),
+            color,
+        });
+    }
+
+    /// A line from `origin` along `direction`, as long as `direction` is.
+    pub fn ray(&mut self, origin: Vec3, direction: Vec3, color: [f32; 4]) {
+        self.line(origin, origin + direction, color);
+    }
+
+    /// The edges of the axis-aligned box spanning `min` to `max`.
+    pub fn aabb(&mut self, min: Vec3, max: Vec3, color: [f32; 4]) {
+        let corner = |x: bool, y: bool, z: bool| {
+            Vec3::new(
+                if x { max.x } else { min.x },
+                if y { max.y } else { min.y },
+                if z { max.z } else { min.z },
+            )
+        };
+
+        for a in [false, true] {
+            for b in [false, true] {
+                self.line(corner(false, a, b), corner(true, a, b), color);
+                self.line(corner(a, false, b), corner(a, true, b), color);
+                self.line(corner(a, b, false), corner(a, b, true), color);
+            }
+        }
+    }
+
+    /// A circle of `radius` around `center`, lying in the plane facing `normal`.
+    pub fn circle(&mut self, center: Vec3, normal: Vec3, radius: f32, color: [f32; 4]) {
+        let normal = normal.normalize_or(Vec3::Y);
+        let (u, v) = normal.any_orthonormal_pair();
+        let point = |i: usize| {
+            let angle = i as f32 / CIRCLE_SEGMENTS as f32 * std::f32::consts::TAU;
+            center + (u * angle.cos() + v * angle.sin()) * radius
+        };
+
+        for i in 0..CIRCLE_SEGMENTS {
+            self.line(point(i), point(i + 1), color);
+        }
+    }
+
+    /// Draws everything added since the last flush into `view`, on top of what is already
+    /// there, as seen through `view_proj` (see [crate::prelude::Camera::view_proj]).
+    pub fn flush(
+        &mut self,
+        ctx: &Context,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        view_proj: Mat4,
+    ) {
+        if self.vertices.is_empty() {
+            return;
+        }
+
+        let graphics = &ctx.graphics;
+        if self.vertices.len() > self.capacity {
+            self.capacity = self.vertices.len().next_power_of_two();
+            self.vertex_buffer = Self::create_vertex_buffer(graphics, self.capacity);
+        }
+
+        graphics.write_buffer_staged(encoder, &self.camera, 0, bytemuck::bytes_of(&view_proj));
+        graphics.write_buffer_staged(encoder, &self.vertex_buffer, 0, bytemuck::cast_slice(&self.vertices));
+
+        {
+            let mut pass = RenderPassBuilder::new(view)
+                .label("Debug Draw Pass")
+                .load()
+                .begin(encoder);
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &self.camera_bind_group, &[]);
+            pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+            pass.draw(0..self.vertices.len() as u32, 0..1);
+        }
+
+        self.vertices.clear();
+    }
+}
@@ -0,0 +1,136 @@
+use slint::winit_030::WinitWindowAccessor;
+use winit::event::{ElementState, WindowEvent};
+
+use crate::{GraphicsContext, pass::RenderPassBuilder};
+
+/// Runs egui over the scene: window events are fed to it before the [crate::input::InputManager]
+/// sees them, and each frame is drawn into the viewport once the app has rendered. It sees
+/// the same events the scene does, so it never reaches outside the viewport.
+///
+/// The egui context lives as long as the app, so window positions and other memory survive a
+/// device loss. Only the renderer is rebuilt with the new device.
+#[derive(Default)]
+pub(crate) struct EguiLayer {
+    ctx: egui::Context,
+    winit: Option<egui_winit::State>,
+    renderer: Option<egui_wgpu::Renderer>,
+}
+
+impl EguiLayer {
+    /// Creates the renderer for a (new) device drawing into views of `format`, and the winit
+    /// state once the window exists.
+    pub(crate) fn attach(&mut self, window: &slint::Window, graphics: &GraphicsContext, format: wgpu::TextureFormat) {
+        self.renderer = Some(egui_wgpu::Renderer::new(
+            &graphics.device,
+            format,
+            egui_wgpu::RendererOptions::default(),
+        ));
+
+        if self.winit.is_none() {
+            let max_texture_side = graphics.device.limits().max_texture_dimension_2d as usize;
+            self.winit = window.with_winit_window(|window| {
+                egui_winit::State::new(
+                    self.ctx.clone(),
+                    egui::ViewportId::ROOT,
+                    window,
+                    Some(window.scale_factor() as f32),
+                    window.theme(),
+                    Some(max_texture_side),
+                )
+            });
+        }
+    }
+
+    /// Hands `event` to egui, returning whether egui wants it for itself, such as a click on one
+    /// of its windows or typing into a text field.
+    ///
+    /// Releases are never claimed, so a button pressed outside egui and released over it
+    /// doesn't stay held down for everything else.
+    pub(crate) fn on_window_event(&mut self, window: &slint::Window, event: &WindowEvent) -> bool {
+        let Some(state) = &mut self.winit else {
+            return false;
+        };
+        let Some(response) = window.with_winit_window(|winit_window| state.on_window_event(winit_window, event)) else {
+            return false;
+        };
+
+        if response.repaint {
+            window.request_redraw();
+        }
+
+        let released = matches!(
+            event,
+            WindowEvent::MouseInput { state: ElementState::Released, .. }
+                | WindowEvent::KeyboardInput { event: winit::event::KeyEvent { state: ElementState::Released, .. }, .. }
+        );
+        response.consumed && !released
+    }
+
+    /// Runs `build_ui` for this frame and draws the result on top of `view`, which covers a
+    /// `(width, height)` viewport at `render_size` pixels.
+    pub(crate) fn frame(
+        &mut self,
+        window: &slint::Window,
+        graphics: &GraphicsContext,
+        view: &wgpu::TextureView,
+        (width, height): (u32, u32),
+        render_size: (u32, u32),
+        build_ui: impl FnMut(&egui::Context),
+    ) {
+        let Self { ctx, winit, renderer } = self;
+        let (Some(state), Some(renderer)) = (winit, renderer) else {
+            return;
+        };
+        let Some(mut raw_input) = window.with_winit_window(|winit_window| state.take_egui_input(winit_window)) else {
+            return;
+        };
+        // egui-winit assumes the whole window, but egui only gets the viewport.
+        let pixels_per_point = window.with_winit_window(|winit_window| egui_winit::pixels_per_point(ctx, winit_window));
+        if let Some(pixels_per_point) = pixels_per_point {
+            let size = egui::vec2(width as f32, height as f32) / pixels_per_point;
+            raw_input.screen_rect = Some(egui::Rect::from_min_size(egui::Pos2::ZERO, size));
+        }
+
+        let output = ctx.run(raw_input, build_ui);
+        window.with_winit_window(|winit_window| state.handle_platform_output(winit_window, output.platform_output));
+
+        // egui asks for a repaint while it animates, which matters when redrawing on demand.
+        if output
+            .viewport_output
+            .get(&egui::ViewportId::ROOT)
+            .is_some_and(|viewport| viewport.repaint_delay.is_zero())
+        {
+            window.request_redraw();
+        }
+
+        // laid out for the window, but drawn at the scene's resolution.
+        let screen = egui_wgpu::ScreenDescriptor {
+            size_in_pixels: render_size.into(),
+            pixels_per_point: output.pixels_per_point * render_size.0 as f32 / width.max(1) as f32,
+        };
+        let primitives = ctx.tessellate(output.shapes, output.pixels_per_point);
+
+        for (id, delta) in &output.textures_delta.set {
+            renderer.update_texture(&graphics.device, &graphics.queue, *id, delta);
+        }
+
+        let mut encoder = graphics.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("egui Encoder"),
+        });
+        let callbacks = renderer.update_buffers(&graphics.device, &graphics.queue, &mut encoder, &primitives, &screen);
+        {
+            let mut pass = RenderPassBuilder::new(view)
+                .label("egui Pass")
+                .load()
+                .begin(&mut encoder)
+                .forget_lifetime();
+            renderer.render(&mut pass, &primitives, &screen);
+        }
+        graphics.queue.submit(callbacks);
+        graphics.submit(encoder);
+
+        for id in &output.textures_delta.free {
+            renderer.free_texture(id);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use std::{
+    ops::{Deref, DerefMut},
+    sync::Arc,
+};
+
+use parking_lot::Mutex;
+
+use crate::GraphicsContext;
+
+/// Command buffers finished by [SpawnedEncoder]s this frame, in the order they were spawned.
+#[derive(Default)]
+struct Slots {
+    /// Bumped every time the slots are submitted, so an encoder finished too late can tell.
+    frame: u64,
+    buffers: Vec<Option<wgpu::CommandBuffer>>,
+}
+
+/// Collects [SpawnedEncoder]s for the app loop to submit in one go once `render` returns.
+#[derive(Clone, Default)]
+pub(crate) struct EncoderQueue(Arc<Mutex<Slots>>);
+
+impl EncoderQueue {
+    pub(crate) fn spawn(&self, ctx: &GraphicsContext, label: &str) -> SpawnedEncoder {
+        let encoder = ctx.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some(label),
+        });
+
+        let mut slots = self.0.lock();
+        slots.buffers.push(None);
+        SpawnedEncoder {
+            encoder,
+            frame: slots.frame,
+            slot: slots.buffers.len() - 1,
+            queue: self.clone(),
+        }
+    }
+
+    /// Submits every encoder finished this frame in spawn order. Ones that were dropped, or
+    /// haven't been finished yet, are left out.
+    pub(crate) fn submit(&self, ctx: &GraphicsContext) {
+        let buffers = {
+            let mut slots = self.0.lock();
+            slots.frame += 1;
+            std::mem::take(&mut slots.buffers)
+        };
+
+        if buffers.iter().any(Option::is_some) {
+            ctx.submit_command_buffers(buffers.into_iter().flatten());
+        }
+    }
+}
+
+/// A command encoder from [crate::prelude::Context::spawn_encoder] that can be filled on
+/// another thread, and is submitted by the app after `render` returns.
+///
+/// Encoders are submitted in the order they were spawned, not the order they were finished,
+/// so spawn them in the order their work has to happen and then record them in parallel. They
+/// go after anything `render` submitted itself, and before the UI is drawn on top. Call
+/// [Self::finish] before `render` returns: dropping an encoder discards it, and one finished
+/// after its frame was submitted is thrown away with a warning.
+///
+/// It derefs to [wgpu::CommandEncoder], so passes are recorded into it as usual.
+pub struct SpawnedEncoder {
+    encoder: wgpu::CommandEncoder,
+    frame: u64,
+    slot: usize,
+    queue: EncoderQueue,
+}
+
+impl SpawnedEncoder {
+    /// Hands the recorded commands back for submitting in this encoder's place.
+    pub fn finish(self) {
+        let buffer = self.encoder.finish();
+
+        let mut slots = self.queue.0.lock();
+        if slots.frame == self.frame {
+            slots.buffers[self.slot] = Some(buffer);
+        } else {
+            log::warn!("A spawned encoder was finished after its frame was submitted, dropping it");
+        }
+    }
+}
+
+impl Deref for SpawnedEncoder {
+    type Target = wgpu::CommandEncoder;
+
+    fn deref(&self) -> &Self::Target {
+        &self.encoder
+    }
+}
+
+impl DerefMut for SpawnedEncoder {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.encoder
+    }
+}
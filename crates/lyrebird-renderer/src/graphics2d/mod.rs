@@ -0,0 +1,5 @@
+//! Helpers for drawing in 2D on top of [crate::GraphicsContext].
+
+mod sprite;
+
+pub use sprite::*;
@@ -0,0 +1,299 @@
+use glam::{Mat4, Vec2};
+
+use crate::{pass::RenderPassBuilder, scene::Context};
+
+/// A textured quad to draw with a [SpriteBatch].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sprite {
+    /// Centre of the sprite in pixels, with (0, 0) at the top left of the view.
+    pub position: Vec2,
+    /// Width and height in pixels.
+    pub size: Vec2,
+    /// Clockwise rotation around the centre, in radians.
+    pub rotation: f32,
+    /// Linear RGBA the texture is multiplied with.
+    pub color: [f32; 4],
+    /// The part of the texture to show, as x, y, width and height in 0..1 uv space.
+    pub source: [f32; 4],
+}
+
+impl Sprite {
+    /// An untinted, unrotated sprite showing the whole texture.
+    pub fn new(position: Vec2, size: Vec2) -> Self {
+        Self {
+            position,
+            size,
+            rotation: 0.0,
+            color: [1.0; 4],
+            source: [0.0, 0.0, 1.0, 1.0],
+        }
+    }
+
+    pub fn rotation(mut self, radians: f32) -> Self {
+        self.rotation = radians;
+        self
+    }
+
+    pub fn color(mut self, color: [f32; 4]) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn source(mut self, source: [f32; 4]) -> Self {
+        self.source = source;
+        self
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct SpriteInstance {
+    position: [f32; 2],
+    size: [f32; 2],
+    rotation: f32,
+    color: [f32; 4],
+    source: [f32; 4],
+}
+
+impl SpriteInstance {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 5] = wgpu::vertex_attr_array![
+        0 => Float32x2,
+        1 => Float32x2,
+        2 => Float32,
+        3 => Float32x4,
+        4 => Float32x4,
+    ];
+
+    fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: size_of::<Self>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+impl From<Sprite> for SpriteInstance {
+    fn from(sprite: Sprite) -> Self {
+        Self {
+            position: sprite.position.into(),
+            size: sprite.size.into(),
+            rotation: sprite.rotation,
+            color: sprite.color,
+            source: sprite.source,
+        }
+    }
+}
+
+/// Collects sprites over a frame and draws all of them in one instanced draw call.
+///
+/// The camera is orthographic in pixels, with (0, 0) at the top left of the view and y
+/// pointing down. Sprites are alpha blended in the order they were added.
+///
+/// Uploads go through the staging belt, so the encoder passed to [Self::flush] has to be
+/// submitted with [crate::GraphicsContext::submit].
+pub struct SpriteBatch {
+    pipeline: wgpu::RenderPipeline,
+    texture_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    camera: wgpu::Buffer,
+    camera_bind_group: wgpu::BindGroup,
+    instances: wgpu::Buffer,
+    capacity: usize,
+    sprites: Vec<SpriteInstance>,
+}
+
+impl SpriteBatch {
+    const INITIAL_CAPACITY: usize = 256;
+
+    /// Creates a batch that draws into views of the viewport's format.
+    pub fn new(ctx: &Context) -> Self {
+        Self::with_format(&ctx.graphics, ctx.viewport_format())
+    }
+
+    /// Creates a batch that draws into views of `format`.
+    pub fn with_format(graphics: &crate::GraphicsContext, format: wgpu::TextureFormat) -> Self {
+        let device = &graphics.device;
+        let shader = device.create_shader_module(wgpu::include_wgsl!("sprite.wgsl"));
+
+        let camera_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("sprite camera bind group layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let texture_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("sprite texture bind group layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("sprite pipeline layout"),
+            bind_group_layouts: &[&camera_layout, &texture_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("sprite pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                compilation_options: Default::default(),
+                buffers: &[SpriteInstance::layout()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                compilation_options: Default::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let camera = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("sprite camera"),
+            size: size_of::<Mat4>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("sprite camera bind group"),
+            layout: &camera_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: camera.as_entire_binding(),
+            }],
+        });
+
+        Self {
+            pipeline,
+            texture_layout,
+            sampler: graphics.linear_clamp(),
+            camera,
+            camera_bind_group,
+            instances: Self::create_instances(graphics, Self::INITIAL_CAPACITY),
+            capacity: Self::INITIAL_CAPACITY,
+            sprites: Vec::with_capacity(Self::INITIAL_CAPACITY),
+        }
+    }
+
+    fn create_instances(graphics: &crate::GraphicsContext, capacity: usize) -> wgpu::Buffer {
+        graphics.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("sprite instances"),
+            size: (capacity * size_of::<SpriteInstance>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// Samples sprite textures with `config` from the next [Self::flush] on, rather than the
+    /// default linear filtering. Pixel art wants [crate::prelude::SamplerConfig::NEAREST_CLAMP].
+    pub fn set_sampler(&mut self, graphics: &crate::GraphicsContext, config: crate::prelude::SamplerConfig) {
+        self.sampler = graphics.sampler(config);
+    }
+
+    /// Queues a sprite for the next [Self::flush].
+    pub fn draw(&mut self, sprite: Sprite) {
+        self.sprites.push(sprite.into());
+    }
+
+    /// Number of sprites waiting for the next [Self::flush].
+    pub fn len(&self) -> usize {
+        self.sprites.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sprites.is_empty()
+    }
+
+    /// Draws every queued sprite with `texture` into `view`, on top of what is already there,
+    /// and empties the batch. Sprites using different textures need a flush each.
+    pub fn flush(
+        &mut self,
+        ctx: &Context,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        texture: &wgpu::TextureView,
+    ) {
+        if self.sprites.is_empty() {
+            return;
+        }
+
+        let graphics = &ctx.graphics;
+        if self.sprites.len() > self.capacity {
+            self.capacity = self.sprites.len().next_power_of_two();
+            self.instances = Self::create_instances(graphics, self.capacity);
+        }
+
+        let (width, height) = ctx.viewport_size();
+        let projection = Mat4::orthographic_rh(0.0, width as f32, height as f32, 0.0, -1.0, 1.0);
+        graphics.write_buffer_staged(encoder, &self.camera, 0, bytemuck::bytes_of(&projection));
+        graphics.write_buffer_staged(encoder, &self.instances, 0, bytemuck::cast_slice(&self.sprites));
+
+        let texture_bind_group = graphics.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("sprite texture bind group"),
+            layout: &self.texture_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(texture),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+            ],
+        });
+
+        {
+            let mut pass = RenderPassBuilder::new(view)
+                .label("Sprite Pass")
+                .load()
+                .begin(encoder);
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &self.camera_bind_group, &[]);
+            pass.set_bind_group(1, &texture_bind_group, &[]);
+            pass.set_vertex_buffer(0, self.instances.slice(..));
+            pass.draw(0..4, 0..self.sprites.len() as u32);
+        }
+
+        self.sprites.clear();
+    }
+}
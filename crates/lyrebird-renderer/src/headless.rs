@@ -0,0 +1,235 @@
+use std::{sync::Arc, time::Duration};
+
+use crate::{GraphicsContext, State, no_adapter_error, input::InputManager, profiler::GpuProfiler, scene::AppBehaviour};
+
+const DEVICE_LABEL: &str = "lyrebird headless device";
+
+/// Requests a hardware adapter, falling back to a software one so headless runs also work on
+/// CI machines without a GPU.
+fn request_adapter(instance: &wgpu::Instance) -> anyhow::Result<wgpu::Adapter> {
+    let request = |force_fallback_adapter| {
+        pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::default(),
+            force_fallback_adapter,
+            compatible_surface: None,
+        }))
+    };
+
+    request(false).or_else(|error| {
+        log::warn!("No hardware adapter ({error}), trying a fallback adapter");
+        request(true).map_err(|error| no_adapter_error(error, true))
+    })
+}
+
+/// Creates a device without a surface, for headless rendering and [crate::prelude::ComputeContext].
+pub(crate) fn create_graphics(instance: &wgpu::Instance, label: &str) -> anyhow::Result<GraphicsContext> {
+    let adapter = request_adapter(instance)?;
+    // the GpuProfiler's timestamps, when the adapter can.
+    let features = adapter.features() & wgpu::Features::TIMESTAMP_QUERY;
+    let (device, queue) = crate::request_device(&adapter, label, features, crate::default_limits(&adapter))?;
+
+    Ok(GraphicsContext::new(instance.clone(), Some(adapter), device, queue))
+}
+
+/// Runs an [AppBehaviour] without a window or surface, rendering `frames` frames into a
+/// `width`x`height` offscreen target and returning the pixels of each frame.
+///
+/// Frames are read back in [State::FORMAT] with tightly packed rows. The scene is driven with
+/// a fixed `dt` of 1/60s so captures are reproducible, which makes this suitable for server-side
+/// rendering and image tests in CI.
+///
+/// A software adapter is used when there is no GPU, and an error explaining what was tried is
+/// returned when there isn't one of those either.
+///
+/// If the device is lost, a new one is created, the scene is told through
+/// [AppBehaviour::device_lost] and the interrupted frame is rendered again.
+pub fn run_headless<S>(width: u32, height: u32, frames: usize) -> anyhow::Result<Vec<Vec<u8>>>
+where
+    S: AppBehaviour<Args = ()>,
+{
+    run_headless_with::<S>((), width, height, frames)
+}
+
+/// Like [run_headless], but constructs the app from `args`.
+pub fn run_headless_with<S>(args: S::Args, width: u32, height: u32, frames: usize) -> anyhow::Result<Vec<Vec<u8>>>
+where
+    S: AppBehaviour,
+{
+    const DT: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+    let instance = wgpu::Instance::default();
+    let ctx = Arc::new(create_graphics(&instance, DEVICE_LABEL)?);
+    let mut state = State {
+        profiler: GpuProfiler::new(&ctx),
+        ctx,
+        input_manager: InputManager::default(),
+        start: web_time::Instant::now(),
+        dt: DT,
+        max_dt: Some(crate::DEFAULT_MAX_DT),
+        dt_clamped: false,
+        clear_color: wgpu::Color::BLACK,
+        viewport_size: (width.max(1), height.max(1)),
+        render_scale: 1.0,
+        surface_ready: true,
+        viewport_format: State::FORMAT,
+        fixed_accumulator: Duration::ZERO,
+        interpolation_alpha: 1.0,
+        shared: Default::default(),
+        encoders: Default::default(),
+    };
+
+    let mut target = state.ctx.create_render_target(width, height, State::FORMAT)?;
+    state.input_manager.set_window_metrics(1.0, winit::dpi::PhysicalSize::new(width, height));
+    let mut app = S::new(args);
+    state.clear_color = app.clear_color();
+    app.init(&state.context());
+
+    let mut captures = Vec::with_capacity(frames);
+    for _ in 0..frames {
+        state.update_gamepads(&mut app);
+        let dt = state.input_manager.begin_frame(DT);
+        state.set_dt(dt);
+        state.clear_color = app.clear_color();
+        app.frame_begin(&state.context());
+        state.fixed_updates(&mut app);
+        let mut ctx = state.context();
+        app.update(&ctx, state.dt.as_secs_f64());
+        app.render(&ctx, target.view());
+        state.encoders.submit(&state.ctx);
+        state.profiler.end_frame(&state.ctx);
+
+        let mut pixels = target.read_pixels(&state.ctx);
+        if state.ctx.is_lost() {
+            log::warn!("Device lost during headless frame, recreating");
+            state.recreate_device(DEVICE_LABEL)?;
+            target = state.ctx.create_render_target(width, height, State::FORMAT)?;
+            ctx = state.context();
+            app.device_lost(&ctx);
+
+            app.render(&ctx, target.view());
+            state.encoders.submit(&state.ctx);
+            pixels = target.read_pixels(&state.ctx);
+        }
+        let pixels = pixels?;
+        if state.ctx.is_out_of_memory() {
+            app.out_of_memory(&state.context());
+            anyhow::bail!("GPU ran out of memory during headless rendering");
+        }
+        captures.push(pixels);
+        state.report_gpu_errors(&mut app);
+        app.frame_end(&ctx);
+        state.input_manager.reset_frame_deltas();
+        state.ctx.end_cache_frame();
+
+        if state.is_exit_requested() {
+            break;
+        }
+    }
+
+    app.exiting(&state.context());
+    Ok(captures)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::prelude::*;
+
+    static ERRORS: AtomicUsize = AtomicUsize::new(0);
+
+    struct InvalidBuffer {
+        frame: usize,
+    }
+
+    impl AppBehaviour for InvalidBuffer {
+        type Args = ();
+
+        fn new(_args: ()) -> Self {
+            Self { frame: 0 }
+        }
+
+        fn init(&mut self, _ctx: &Context) {}
+
+        fn update(&mut self, _ctx: &Context, _dt: f64) {}
+
+        fn render(&mut self, ctx: &Context, _view: &wgpu::TextureView) {
+            self.frame += 1;
+            if self.frame == 1 {
+                // MAP_READ may only go with COPY_DST.
+                let _ = ctx.graphics.device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("invalid buffer"),
+                    size: 4,
+                    usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::STORAGE,
+                    mapped_at_creation: false,
+                });
+            }
+        }
+
+        fn gpu_error(&mut self, _ctx: &Context, error: &wgpu::Error) {
+            assert!(matches!(error, wgpu::Error::Validation { .. }));
+            ERRORS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    static DEVICE_LOSSES: AtomicUsize = AtomicUsize::new(0);
+
+    /// Clears to red, losing the device while rendering its second frame.
+    struct LosesDevice {
+        frame: usize,
+    }
+
+    impl AppBehaviour for LosesDevice {
+        type Args = ();
+
+        fn new(_args: ()) -> Self {
+            Self { frame: 0 }
+        }
+
+        fn init(&mut self, _ctx: &Context) {}
+
+        fn update(&mut self, _ctx: &Context, _dt: f64) {
+            self.frame += 1;
+        }
+
+        fn clear_color(&self) -> wgpu::Color {
+            wgpu::Color::RED
+        }
+
+        fn render(&mut self, ctx: &Context, view: &wgpu::TextureView) {
+            if self.frame == 2 && DEVICE_LOSSES.load(Ordering::Relaxed) == 0 {
+                ctx.graphics.simulate_device_loss();
+                return;
+            }
+            let mut encoder = ctx.graphics.device.create_command_encoder(&Default::default());
+            drop(ctx.begin_clear_pass(&mut encoder, view));
+            ctx.graphics.queue.submit([encoder.finish()]);
+        }
+
+        fn device_lost(&mut self, ctx: &Context) {
+            assert!(!ctx.graphics.is_lost());
+            DEVICE_LOSSES.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn recovers_from_device_loss() -> anyhow::Result<()> {
+        let frames = crate::run_headless::<LosesDevice>(2, 2, 4)?;
+        assert_eq!(DEVICE_LOSSES.load(Ordering::Relaxed), 1);
+        assert_eq!(frames.len(), 4);
+        // every frame, the interrupted one included, is the scene's red.
+        // 1.0 as a little-endian f16, the red channel of State::FORMAT.
+        for frame in &frames {
+            assert_eq!(frame[..2], [0x00, 0x3c]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn validation_errors_reach_the_app_without_panicking() -> anyhow::Result<()> {
+        let frames = crate::run_headless::<InvalidBuffer>(4, 4, 3)?;
+        assert_eq!(frames.len(), 3);
+        assert_eq!(ERRORS.load(Ordering::Relaxed), 1);
+        Ok(())
+    }
+}
@@ -1,22 +1,54 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{HashMap, HashSet, VecDeque},
     sync::Arc,
+    time::Duration,
 };
 
-use parking_lot::Mutex;
+use parking_lot::{Mutex, RwLock};
+use web_time::Instant;
 
 #[cfg(not(target_arch = "wasm32"))]
 use gilrs::{Axis, Button, EventType, GamepadId, Gilrs};
 use winit::{
-    dpi::PhysicalPosition,
-    event::{ElementState, MouseButton, MouseScrollDelta, WindowEvent},
-    keyboard::{KeyCode, PhysicalKey},
+    dpi::{LogicalPosition, PhysicalPosition, PhysicalSize},
+    event::{DeviceEvent, ElementState, Ime, MouseButton, MouseScrollDelta, WindowEvent},
+    keyboard::{Key, KeyCode, ModifiersState, PhysicalKey, SmolStr},
 };
 
-#[derive(Debug, Clone)]
+use crate::replay::{InputRecording, RecordedEvent, Replay};
+
+/// Battery state of a gamepad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PowerState {
+    /// The gamepad has no battery.
+    Wired,
+    /// Plugged in and charging, with the battery level in percent.
+    Charging(u8),
+    /// Running off the battery, with the battery level in percent.
+    Discharging(u8),
+    /// The platform can't tell (always the case on the web).
+    #[default]
+    Unknown,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl From<gilrs::PowerInfo> for PowerState {
+    fn from(info: gilrs::PowerInfo) -> Self {
+        match info {
+            gilrs::PowerInfo::Wired => Self::Wired,
+            gilrs::PowerInfo::Charging(level) => Self::Charging(level),
+            gilrs::PowerInfo::Charged => Self::Charging(100),
+            gilrs::PowerInfo::Discharging(level) => Self::Discharging(level),
+            gilrs::PowerInfo::Unknown => Self::Unknown,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct GamepadInfo {
     pub name: String,
     pub is_connected: bool,
+    pub power_info: PowerState,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -30,20 +62,240 @@ pub struct GamepadState {
 #[derive(Debug, Clone)]
 pub struct GamepadsSnapshot {
     pub gamepads: HashMap<GamepadId, GamepadState>,
+    pub players: PlayerSlots,
 }
 
-impl Default for GamepadInfo {
-    fn default() -> Self {
-        Self {
-            name: String::new(),
-            is_connected: false,
+/// Stable player indices for connected gamepads, so scenes can route "Player 1" and "Player 2"
+/// without keeping track of raw [GamepadId]s.
+///
+/// Gamepads take the lowest free slot when they connect, so the first controller is player 0.
+/// Unplugging one frees its slot for the next controller to connect, while everyone else keeps
+/// theirs.
+#[derive(Debug, Clone, Default)]
+pub struct PlayerSlots {
+    slots: Vec<Option<GamepadId>>,
+}
+
+impl PlayerSlots {
+    /// Gives `id` the lowest free slot, or returns the one it already has.
+    fn assign(&mut self, id: GamepadId) -> usize {
+        if let Some(slot) = self.slot_for(id) {
+            return slot;
+        }
+
+        match self.slots.iter().position(Option::is_none) {
+            Some(slot) => {
+                self.slots[slot] = Some(id);
+                slot
+            }
+            None => {
+                self.slots.push(Some(id));
+                self.slots.len() - 1
+            }
+        }
+    }
+
+    fn release(&mut self, id: GamepadId) {
+        if let Some(slot) = self.slot_for(id) {
+            self.slots[slot] = None;
+        }
+    }
+
+    pub fn slot_for(&self, id: GamepadId) -> Option<usize> {
+        self.slots.iter().position(|slot| *slot == Some(id))
+    }
+
+    pub fn gamepad_for_slot(&self, slot: usize) -> Option<GamepadId> {
+        self.slots.get(slot).copied().flatten()
+    }
+
+    /// Every occupied slot with its gamepad, in slot order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, GamepadId)> + '_ {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(slot, id)| id.map(|id| (slot, id)))
+    }
+
+    /// How many players currently have a gamepad.
+    pub fn len(&self) -> usize {
+        self.slots.iter().flatten().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// One of the eight ways a stick or D-pad can point, for menus and grid movement. See
+/// [InputManager::stick_direction] and [InputManager::dpad_direction].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+impl Direction {
+    /// The direction `(x, y)` points in, with y pointing up as gamepad axes do, or `None` while
+    /// it is within `deadzone` (0..1) of the centre. Each direction covers a 45° slice.
+    pub fn from_axes(x: f32, y: f32, deadzone: f32) -> Option<Self> {
+        if !x.is_finite() || !y.is_finite() || x.hypot(y) < deadzone.max(f32::EPSILON) {
+            return None;
+        }
+
+        let octant = (y.atan2(x) / std::f32::consts::FRAC_PI_4).round() as i32;
+        Some(match octant.rem_euclid(8) {
+            0 => Self::Right,
+            1 => Self::UpRight,
+            2 => Self::Up,
+            3 => Self::UpLeft,
+            4 => Self::Left,
+            5 => Self::DownLeft,
+            6 => Self::Down,
+            _ => Self::DownRight,
+        })
+    }
+
+    /// Like [Self::from_axes], but only ever up, down, left or right, going with whichever axis
+    /// is pushed furthest.
+    pub fn from_axes_four_way(x: f32, y: f32, deadzone: f32) -> Option<Self> {
+        Self::from_axes(x, y, deadzone)?;
+        Some(match (x.abs() > y.abs(), x > 0.0, y > 0.0) {
+            (true, true, _) => Self::Right,
+            (true, false, _) => Self::Left,
+            (false, _, true) => Self::Up,
+            (false, _, false) => Self::Down,
+        })
+    }
+
+    /// The step this direction takes along each axis, with y pointing up: `(1, 1)` for
+    /// [Self::UpRight].
+    pub fn offset(self) -> (i32, i32) {
+        match self {
+            Self::Up => (0, 1),
+            Self::UpRight => (1, 1),
+            Self::Right => (1, 0),
+            Self::DownRight => (1, -1),
+            Self::Down => (0, -1),
+            Self::DownLeft => (-1, -1),
+            Self::Left => (-1, 0),
+            Self::UpLeft => (-1, 1),
+        }
+    }
+}
+
+/// One of a gamepad's two analog sticks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stick {
+    Left,
+    Right,
+}
+
+impl Stick {
+    /// The x and y axes the stick reports on.
+    pub fn axes(self) -> (Axis, Axis) {
+        match self {
+            Self::Left => (Axis::LeftStickX, Axis::LeftStickY),
+            Self::Right => (Axis::RightStickX, Axis::RightStickY),
         }
     }
 }
 
+/// A key pressed together with an exact set of modifiers, such as Ctrl+S, for keybinds that
+/// can be stored and changed.
+///
+/// Modifiers match exactly, so Ctrl+S doesn't fire for Ctrl+Shift+S, and left and right
+/// modifier keys count the same.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Shortcut {
+    pub modifiers: ModifiersState,
+    pub key: KeyCode,
+}
+
+impl Shortcut {
+    pub fn new(modifiers: ModifiersState, key: KeyCode) -> Self {
+        Self { modifiers, key }
+    }
+
+    /// `key` on its own, with no modifiers held.
+    pub fn key(key: KeyCode) -> Self {
+        Self::new(ModifiersState::empty(), key)
+    }
+
+    /// Ctrl + `key`.
+    pub fn ctrl(key: KeyCode) -> Self {
+        Self::new(ModifiersState::CONTROL, key)
+    }
+
+    /// Whether this shortcut was pressed this frame, see
+    /// [InputManager::was_shortcut_just_pressed].
+    pub fn was_just_pressed(&self, input: &InputManager) -> bool {
+        input.was_shortcut_just_pressed(self.modifiers, self.key)
+    }
+}
+
+/// Pixels per line of scroll, roughly one line of body text.
+const DEFAULT_SCROLL_LINE_HEIGHT: f32 = 20.0;
+
+/// How long smoothed scrolling takes to cover half of the distance still to go, by default.
+const DEFAULT_SCROLL_HALF_LIFE: Duration = Duration::from_millis(50);
+/// Longest gap between two clicks that still counts as a double-click, close to the usual
+/// desktop default.
+const DEFAULT_DOUBLE_CLICK_TIME: Duration = Duration::from_millis(400);
+/// How far, in physical pixels, the cursor may wander between the clicks of a double-click.
+const DEFAULT_DOUBLE_CLICK_DISTANCE: f64 = 4.0;
+/// How far a stick has to be pushed before it points in a [Direction]. Halfway keeps a resting
+/// or drifting stick from wandering through menus.
+const DEFAULT_DIRECTION_DEADZONE: f32 = 0.5;
+/// Sticks and triggers send an event per tiny movement, so this leaves plenty of room for a
+/// frame of ordinary play while a backlog still drains over a few frames.
+#[cfg(not(target_arch = "wasm32"))]
+const DEFAULT_GAMEPAD_EVENT_LIMIT: usize = 256;
+
+/// A kind of input a UI can claim, see [InputManager::set_captured].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputKind {
+    /// Keys, text and the IME.
+    Keyboard,
+    /// The cursor, mouse buttons, scrolling and raw mouse motion.
+    Pointer,
+}
+
+/// Where and when a mouse button was last released, for spotting double-clicks.
+#[derive(Clone, Copy)]
+struct Click {
+    at: Instant,
+    position: Option<PhysicalPosition<f64>>,
+}
+
+/// Who takes care of gamepad stick deadzones and jitter, picked with
+/// [crate::AppBuilder::gamepad_axis_filtering].
+///
+/// Either way axis values are the ones gilrs has calibrated to -1..1 using what the platform
+/// reports about each axis' range, and any NaN a quirky device produces is read as 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AxisFiltering {
+    /// gilrs' default filters: readings inside each axis' deadzone, as the hardware or the OS
+    /// reports it, are zeroed (radially for sticks), tiny jitters are dropped, and D-pads that
+    /// report as axes also press the D-pad buttons.
+    #[default]
+    Gilrs,
+    /// Axis values straight from the device, with no deadzone or jitter filtering, for apps
+    /// that apply their own. [crate::prelude::InputManager::axis_deadzone] still tells what gilrs
+    /// would have used.
+    Raw,
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 fn normalize_axis_value(value: f32) -> f32 {
-    // gilrs can occasionally produce NaN on device quirks; keep consumers safe.
+    // gilrs can occasionally produce NaN on device quirks; keep consumers safe. It's already
+    // calibrated to -1..1, so the clamp only catches devices that overshoot their range.
     if value.is_finite() {
         value.clamp(-1.0, 1.0)
     } else {
@@ -51,51 +303,244 @@ fn normalize_axis_value(value: f32) -> f32 {
     }
 }
 
+/// A gamepad (dis)connection seen while pumping gilrs, for dispatching to the scene.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) enum GamepadConnection {
+    Connected(GamepadId, GamepadInfo),
+    Disconnected(GamepadId),
+}
+
 #[derive(Default)]
 struct GamepadFrameDeltas {
     just_pressed: HashSet<(GamepadId, Button)>,
     just_released: HashSet<(GamepadId, Button)>,
+    /// Analog button values as they were at the end of the previous frame, for detecting when a
+    /// trigger crosses a threshold.
+    previous_values: HashMap<(GamepadId, Button), f32>,
+    /// Axis values at the end of the previous frame, for spotting a stick entering a direction.
+    previous_axes: HashMap<(GamepadId, Axis), f32>,
+    split: FrameSplit<GamepadId>,
+}
+
+/// What a gamepad event means for splitting a backlog across frames.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SplitKind {
+    Pressed(Button),
+    Released(Button),
+    /// A connection or disconnection, which the event limit doesn't apply to.
+    Connection,
+    Other,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl SplitKind {
+    fn of(event: &EventType) -> Self {
+        match *event {
+            EventType::ButtonPressed(button, _) => Self::Pressed(button),
+            EventType::ButtonReleased(button, _) => Self::Released(button),
+            EventType::Connected | EventType::Disconnected => Self::Connection,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Which gamepad events from a backlog go into the current frame, see
+/// [InputInner::pump_gilrs_events]. Generic over the gamepad id, which gilrs has no way to
+/// make up outside of a real device.
+#[cfg(not(target_arch = "wasm32"))]
+struct FrameSplit<I> {
+    pressed: HashSet<(I, Button)>,
+    released: HashSet<(I, Button)>,
+    /// How many events the frame has taken, for the event limit.
+    applied: usize,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<I> Default for FrameSplit<I> {
+    fn default() -> Self {
+        Self {
+            pressed: HashSet::new(),
+            released: HashSet::new(),
+            applied: 0,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<I: Copy + Eq + std::hash::Hash> FrameSplit<I> {
+    /// Takes the event at the front of `backlog` if it still fits in the frame. A frame is full
+    /// after `limit` events, or at an event repeating a press or release it already has for
+    /// the same button. Connections always fit and don't count towards the limit, so a flood of
+    /// stick movements can't hold up a gamepad (dis)connecting.
+    fn next<E>(&mut self, backlog: &mut VecDeque<E>, limit: Option<usize>, kind: impl Fn(&E) -> (I, SplitKind)) -> Option<E> {
+        let (id, kind) = kind(backlog.front()?);
+        if kind != SplitKind::Connection {
+            if limit.is_some_and(|limit| self.applied >= limit) {
+                return None;
+            }
+            let fits = match kind {
+                SplitKind::Pressed(button) => self.pressed.insert((id, button)),
+                SplitKind::Released(button) => self.released.insert((id, button)),
+                _ => true,
+            };
+            if !fits {
+                return None;
+            }
+            self.applied += 1;
+        }
+        backlog.pop_front()
+    }
+
+    fn reset(&mut self) {
+        self.pressed.clear();
+        self.released.clear();
+        self.applied = 0;
+    }
 }
 
 struct InputInner {
+    /// Only ever touched under the write lock; the mutex is there because `Gilrs` isn't `Sync`,
+    /// which the read lock would otherwise require.
     #[cfg(not(target_arch = "wasm32"))]
-    gilrs: Gilrs,
+    gilrs: Mutex<Gilrs>,
 
-    /// Any events that may have not being covered, you can cover yourself.
+    /// Any events that may have not being covered, you can cover yourself. For events that
+    /// aren't input at all, see [crate::prelude::AppBehaviour::window_event].
     latest_event: Option<WindowEvent>,
+    /// Every input event from this frame, in the order they came in.
+    events: Vec<WindowEvent>,
+    replay: Replay,
 
     /// Keys currently held down (tracked via `KeyCode`).
     keys_down: HashSet<KeyCode>,
+    /// When each held key went down.
+    key_pressed_at: HashMap<KeyCode, Instant>,
     /// Mouse buttons currently held down.
     mouse_buttons_down: HashSet<MouseButton>,
+    /// When each held mouse button went down.
+    mouse_pressed_at: HashMap<MouseButton, Instant>,
     /// Most recent cursor position.
     cursor_position: Option<PhysicalPosition<f64>>,
-    /// Scroll delta accumulated since last `reset_frame_deltas()`.
-    scroll_delta: (f32, f32),
+    /// Whether the cursor is over the window, between `CursorEntered` (or the first move) and
+    /// `CursorLeft`.
+    cursor_entered: bool,
+    /// Scale factor of the window, for converting to logical coordinates.
+    scale_factor: f64,
+    /// Size of the window, for normalising the cursor position.
+    window_size: PhysicalSize<u32>,
+    /// Pixel-precise scroll (trackpads, high-res wheels) accumulated since last
+    /// `reset_frame_deltas()`.
+    scroll_pixels: (f32, f32),
+    /// Line-based scroll (notched mouse wheels) accumulated since last `reset_frame_deltas()`.
+    scroll_lines: (f32, f32),
+    /// How many pixels one line of scroll counts as in `scroll_delta()`.
+    scroll_line_height: f32,
+    /// The part of `scroll_pixels.1` and `scroll_lines.1` that was purely vertical and
+    /// happened with shift held, for `scroll_delta()` to turn sideways.
+    shift_scroll: (f32, f32),
+    /// Whether `scroll_delta()` treats shift + vertical scroll as horizontal.
+    shift_scroll_horizontal: bool,
+    /// Scrolling `smoothed_scroll` has yet to hand out, in pixels.
+    scroll_remaining: (f32, f32),
+    /// This frame's share of `scroll_remaining`, worked out in `begin_frame()`.
+    smoothed_scroll: (f32, f32),
+    /// How long smoothed scrolling takes to cover half of what's left.
+    scroll_half_life: Duration,
+    /// Raw mouse motion from `DeviceEvent::MouseMotion` accumulated since last
+    /// `reset_frame_deltas()`, unaffected by pointer acceleration or the window's edges.
+    mouse_motion: (f64, f64),
+    /// Modifier keys currently held, as of the last `ModifiersChanged`.
+    modifiers: ModifiersState,
+    /// Keys that went down this frame, in the order they were pressed. Auto-repeat is ignored.
+    keys_just_pressed: Vec<KeyCode>,
+    /// The same presses, with the modifiers held at the time.
+    shortcuts_just_pressed: Vec<Shortcut>,
     /// Last key event this frame (if any).
     last_key: Option<(KeyCode, ElementState)>,
+    /// Logical keys currently held, by the physical key that produced them, so a release
+    /// clears its press even if the modifiers changed in between (`Z` pressed, `z` released).
+    logical_keys_down: HashMap<PhysicalKey, Key>,
+    /// Text produced by the last key press this frame (if any).
+    last_text: Option<SmolStr>,
+    /// Text the IME is composing, with the byte range of its cursor or selection.
+    ime_preedit: Option<(String, Option<(usize, usize)>)>,
+    /// Text the IME committed this frame, in order.
+    ime_commit: String,
     /// Last mouse button event this frame (if any).
     last_mouse_button: Option<(MouseButton, ElementState)>,
+    /// The click each button's next release would pair up with into a double-click.
+    last_click: HashMap<MouseButton, Click>,
+    /// Buttons that completed a double-click this frame.
+    double_clicks: HashSet<MouseButton>,
+    double_click_time: Duration,
+    double_click_distance: f64,
+    direction_deadzone: f32,
+    /// Input the UI has claimed, which the `_uncaptured` queries treat as idle.
+    captured: HashSet<InputKind>,
+    /// Where the cursor was when each held button went down.
+    drags: HashMap<MouseButton, PhysicalPosition<f64>>,
+    /// Drags that finished this frame, with where they started and ended.
+    ended_drags: HashMap<MouseButton, (PhysicalPosition<f64>, PhysicalPosition<f64>)>,
 
     #[cfg(not(target_arch = "wasm32"))]
     gamepads: HashMap<GamepadId, GamepadState>,
     #[cfg(not(target_arch = "wasm32"))]
     gamepad_frame: GamepadFrameDeltas,
+    #[cfg(not(target_arch = "wasm32"))]
+    players: PlayerSlots,
+    /// When each held gamepad button went down.
+    #[cfg(not(target_arch = "wasm32"))]
+    gamepad_pressed_at: HashMap<(GamepadId, Button), Instant>,
+    /// Connections and disconnections not yet handed to the scene.
+    #[cfg(not(target_arch = "wasm32"))]
+    gamepad_connections: Vec<GamepadConnection>,
+    /// Every gilrs event from this frame, untouched, for whoever wants more than the mapped
+    /// state (gyros, touchpads, vendor-specific buttons).
+    #[cfg(not(target_arch = "wasm32"))]
+    gamepad_events: Vec<gilrs::Event>,
+    /// Events taken from gilrs that belong to a later frame, oldest first.
+    #[cfg(not(target_arch = "wasm32"))]
+    gamepad_backlog: VecDeque<gilrs::Event>,
+    #[cfg(not(target_arch = "wasm32"))]
+    gamepad_event_limit: Option<usize>,
 }
 
 impl InputInner {
-    fn new() -> Self {
+    /// `mappings` are SDL `gamecontrollerdb` lines to use on top of the database gilrs ships
+    /// with. They're ignored on the web, where the browser maps gamepads itself.
+    #[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+    fn new(mappings: &str, filtering: AxisFiltering) -> Self {
         #[cfg(not(target_arch = "wasm32"))]
         {
-            let gilrs = Gilrs::new().expect("failed to initialize gilrs");
+            for line in mappings.lines().map(str::trim) {
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                match line.split(',').nth(1) {
+                    Some(name) => log::info!("Loaded gamepad mapping for {name}"),
+                    None => log::warn!("Ignoring malformed gamepad mapping: {line}"),
+                }
+            }
+
+            let gilrs = gilrs::GilrsBuilder::new()
+                .add_mappings(mappings)
+                .with_default_filters(filtering == AxisFiltering::Gilrs)
+                .build()
+                .expect("failed to initialize gilrs");
             let mut gamepads: HashMap<GamepadId, GamepadState> = HashMap::new();
+            let mut players = PlayerSlots::default();
 
             // Seed state with already-connected controllers (controllers present before launch).
             for (id, gamepad) in gilrs.gamepads() {
                 let info = GamepadInfo {
                     name: gamepad.name().to_string(),
                     is_connected: gamepad.is_connected(),
+                    power_info: gamepad.power_info().into(),
                 };
+                if info.is_connected {
+                    players.assign(id);
+                }
 
                 let state = GamepadState {
                     info,
@@ -106,16 +551,52 @@ impl InputInner {
             }
 
             Self {
-                gilrs,
+                gilrs: Mutex::new(gilrs),
                 latest_event: None,
+                events: Vec::new(),
+                replay: Replay::default(),
                 keys_down: HashSet::new(),
+                key_pressed_at: HashMap::new(),
                 mouse_buttons_down: HashSet::new(),
+                mouse_pressed_at: HashMap::new(),
                 cursor_position: None,
-                scroll_delta: (0.0, 0.0),
+                cursor_entered: false,
+                scale_factor: 1.0,
+                window_size: PhysicalSize::new(0, 0),
+                scroll_pixels: (0.0, 0.0),
+                scroll_lines: (0.0, 0.0),
+                scroll_line_height: DEFAULT_SCROLL_LINE_HEIGHT,
+                shift_scroll: (0.0, 0.0),
+                shift_scroll_horizontal: false,
+                scroll_remaining: (0.0, 0.0),
+                smoothed_scroll: (0.0, 0.0),
+                scroll_half_life: DEFAULT_SCROLL_HALF_LIFE,
+                mouse_motion: (0.0, 0.0),
+                modifiers: ModifiersState::empty(),
+                keys_just_pressed: Vec::new(),
+                shortcuts_just_pressed: Vec::new(),
                 last_key: None,
+                logical_keys_down: HashMap::new(),
+                last_text: None,
+                ime_preedit: None,
+                ime_commit: String::new(),
                 last_mouse_button: None,
+                last_click: HashMap::new(),
+                double_clicks: HashSet::new(),
+                double_click_time: DEFAULT_DOUBLE_CLICK_TIME,
+                double_click_distance: DEFAULT_DOUBLE_CLICK_DISTANCE,
+                direction_deadzone: DEFAULT_DIRECTION_DEADZONE,
+                captured: HashSet::new(),
+                drags: HashMap::new(),
+                ended_drags: HashMap::new(),
                 gamepads,
                 gamepad_frame: GamepadFrameDeltas::default(),
+                players,
+                gamepad_pressed_at: HashMap::new(),
+                gamepad_connections: Vec::new(),
+                gamepad_events: Vec::new(),
+                gamepad_backlog: VecDeque::new(),
+                gamepad_event_limit: Some(DEFAULT_GAMEPAD_EVENT_LIMIT),
             }
         }
 
@@ -123,220 +604,1182 @@ impl InputInner {
         {
             Self {
                 latest_event: None,
+                events: Vec::new(),
+                replay: Replay::default(),
                 keys_down: HashSet::new(),
+                key_pressed_at: HashMap::new(),
                 mouse_buttons_down: HashSet::new(),
+                mouse_pressed_at: HashMap::new(),
                 cursor_position: None,
-                scroll_delta: (0.0, 0.0),
+                cursor_entered: false,
+                scale_factor: 1.0,
+                window_size: PhysicalSize::new(0, 0),
+                scroll_pixels: (0.0, 0.0),
+                scroll_lines: (0.0, 0.0),
+                scroll_line_height: DEFAULT_SCROLL_LINE_HEIGHT,
+                shift_scroll: (0.0, 0.0),
+                shift_scroll_horizontal: false,
+                scroll_remaining: (0.0, 0.0),
+                smoothed_scroll: (0.0, 0.0),
+                scroll_half_life: DEFAULT_SCROLL_HALF_LIFE,
+                mouse_motion: (0.0, 0.0),
+                modifiers: ModifiersState::empty(),
+                keys_just_pressed: Vec::new(),
+                shortcuts_just_pressed: Vec::new(),
                 last_key: None,
+                logical_keys_down: HashMap::new(),
+                last_text: None,
+                ime_preedit: None,
+                ime_commit: String::new(),
                 last_mouse_button: None,
+                last_click: HashMap::new(),
+                double_clicks: HashSet::new(),
+                double_click_time: DEFAULT_DOUBLE_CLICK_TIME,
+                double_click_distance: DEFAULT_DOUBLE_CLICK_DISTANCE,
+                direction_deadzone: DEFAULT_DIRECTION_DEADZONE,
+                captured: HashSet::new(),
+                drags: HashMap::new(),
+                ended_drags: HashMap::new(),
             }
         }
     }
 
-    #[cfg(not(target_arch = "wasm32"))]
-    fn refresh_gamepad_info(&mut self, id: GamepadId) {
-        let gamepad = self.gilrs.gamepad(id);
-        let entry = self.gamepads.entry(id).or_insert_with(|| GamepadState {
-            info: GamepadInfo::default(),
-            ..Default::default()
-        });
-        entry.info.name = gamepad.name().to_string();
-        entry.info.is_connected = gamepad.is_connected();
-    }
-
-    #[cfg(not(target_arch = "wasm32"))]
-    fn pump_gilrs_events(&mut self) {
-        self.gamepad_frame.just_pressed.clear();
-        self.gamepad_frame.just_released.clear();
-
-        while let Some(ev) = self.gilrs.next_event() {
-            let id = ev.id;
-            match ev.event {
-                EventType::Connected => {
-                    self.refresh_gamepad_info(id);
-                }
-                EventType::Disconnected => {
-                    self.refresh_gamepad_info(id);
+    /// Updates the tracked state for `event`, whether it just came in or is being replayed.
+    fn apply(&mut self, event: &RecordedEvent) {
+        match event {
+            RecordedEvent::Key { physical_key, logical_key, state, text } => {
+                if let PhysicalKey::Code(code) = *physical_key {
+                    self.last_key = Some((code, *state));
+                    match state {
+                        ElementState::Pressed => {
+                            if self.keys_down.insert(code) {
+                                self.key_pressed_at.insert(code, Instant::now());
+                                self.keys_just_pressed.push(code);
+                                self.shortcuts_just_pressed.push(Shortcut::new(self.modifiers, code));
+                            }
+                        }
+                        ElementState::Released => {
+                            self.keys_down.remove(&code);
+                            self.key_pressed_at.remove(&code);
+                        }
+                    }
                 }
-                EventType::ButtonPressed(button, _) => {
-                    self.refresh_gamepad_info(id);
-                    let state = self.gamepads.entry(id).or_default();
-                    state.buttons_down.insert(button);
-                    self.gamepad_frame.just_pressed.insert((id, button));
+
+                match state {
+                    ElementState::Pressed => {
+                        self.logical_keys_down.insert(*physical_key, logical_key.clone());
+                        if let Some(text) = text {
+                            self.last_text = Some(text.clone());
+                        }
+                    }
+                    ElementState::Released => {
+                        self.logical_keys_down.remove(physical_key);
+                    }
                 }
-                EventType::ButtonReleased(button, _) => {
-                    self.refresh_gamepad_info(id);
-                    if let Some(state) = self.gamepads.get_mut(&id) {
-                        state.buttons_down.remove(&button);
+            }
+            RecordedEvent::CursorMoved(position) => {
+                self.cursor_position = Some(*position);
+                self.cursor_entered = true;
+            }
+            // the position comes with the first move after entering.
+            RecordedEvent::CursorEntered => {
+                self.cursor_entered = true;
+            }
+            RecordedEvent::CursorLeft => {
+                self.cursor_position = None;
+                self.cursor_entered = false;
+            }
+            RecordedEvent::MouseButton { button, state } => {
+                self.last_mouse_button = Some((*button, *state));
+                match state {
+                    ElementState::Pressed => {
+                        if self.mouse_buttons_down.insert(*button) {
+                            self.mouse_pressed_at.insert(*button, Instant::now());
+                        }
+                        if let Some(position) = self.cursor_position {
+                            self.drags.insert(*button, position);
+                        }
                     }
-                    self.gamepad_frame.just_released.insert((id, button));
+                    ElementState::Released => {
+                        self.mouse_buttons_down.remove(button);
+                        self.mouse_pressed_at.remove(button);
+                        self.register_click(*button);
+                        self.end_drag(*button);
+                    }
+                }
+            }
+            RecordedEvent::Focused(false) => {
+                // the release of a key, button or modifier held while focus moves away never
+                // reaches us, so let go of everything here rather than leave it stuck.
+                let held: Vec<_> = self.mouse_buttons_down.drain().collect();
+                for button in held {
+                    self.end_drag(button);
                 }
-                EventType::ButtonChanged(button, value, _) => {
-                    self.refresh_gamepad_info(id);
-                    let state = self.gamepads.entry(id).or_default();
-                    state.button_values.insert(button, value.clamp(0.0, 1.0));
+                self.mouse_pressed_at.clear();
+                self.keys_down.clear();
+                self.key_pressed_at.clear();
+                self.logical_keys_down.clear();
+                self.modifiers = ModifiersState::empty();
+            }
+            RecordedEvent::Focused(true) => {}
+            RecordedEvent::Ime(ime) => match ime {
+                Ime::Preedit(text, _) if text.is_empty() => self.ime_preedit = None,
+                Ime::Preedit(text, cursor) => self.ime_preedit = Some((text.clone(), *cursor)),
+                Ime::Commit(text) => {
+                    self.ime_preedit = None;
+                    self.ime_commit.push_str(text);
                 }
-                EventType::AxisChanged(axis, value, _) => {
-                    self.refresh_gamepad_info(id);
-                    let state = self.gamepads.entry(id).or_default();
-                    state.axes.insert(axis, normalize_axis_value(value));
+                Ime::Enabled => {}
+                Ime::Disabled => self.ime_preedit = None,
+            },
+            RecordedEvent::Modifiers(modifiers) => {
+                self.modifiers = *modifiers;
+            }
+            RecordedEvent::MouseWheel(delta) => {
+                // some platforms (macOS) already turn shift + scroll sideways, which shows up
+                // here as horizontal scroll, so only purely vertical scroll is remembered.
+                let shifted = self.modifiers.shift_key();
+                match delta {
+                    MouseScrollDelta::LineDelta(x, y) => {
+                        self.scroll_lines.0 += *x;
+                        self.scroll_lines.1 += *y;
+                        if shifted && *x == 0.0 {
+                            self.shift_scroll.1 += *y;
+                        }
+                    }
+                    MouseScrollDelta::PixelDelta(pos) => {
+                        self.scroll_pixels.0 += pos.x as f32;
+                        self.scroll_pixels.1 += pos.y as f32;
+                        if shifted && pos.x == 0.0 {
+                            self.shift_scroll.0 += pos.y as f32;
+                        }
+                    }
                 }
-                _ => {}
+            }
+            RecordedEvent::MouseMotion(x, y) => {
+                self.mouse_motion.0 += x;
+                self.mouse_motion.1 += y;
             }
         }
     }
-}
 
-/// A manager for input.
-pub struct InputManager {
-    inner: Arc<Mutex<InputInner>>,
-}
+    /// Records a release of `button`, pairing it with the previous one if it was close enough
+    /// in time and space.
+    fn register_click(&mut self, button: MouseButton) {
+        let click = Click {
+            at: Instant::now(),
+            position: self.cursor_position,
+        };
 
-impl Clone for InputManager {
-    fn clone(&self) -> Self {
-        Self {
-            inner: self.inner.clone(),
+        let is_double = self.last_click.get(&button).is_some_and(|previous| {
+            let near = match (previous.position, click.position) {
+                (Some(a), Some(b)) => (a.x - b.x).hypot(a.y - b.y) <= self.double_click_distance,
+                _ => true,
+            };
+            click.at.duration_since(previous.at) <= self.double_click_time && near
+        });
+
+        if is_double {
+            // the pair is used up, so a third click starts a new double-click rather than
+            // completing a second one.
+            self.last_click.remove(&button);
+            self.double_clicks.insert(button);
+        } else {
+            self.last_click.insert(button, click);
         }
     }
-}
 
-impl Default for InputManager {
-    fn default() -> Self {
-        Self {
-            inner: Arc::new(Mutex::new(InputInner::new())),
+    fn end_drag(&mut self, button: MouseButton) {
+        if let Some(start) = self.drags.remove(&button) {
+            let end = self.cursor_position.unwrap_or(start);
+            self.ended_drags.insert(button, (start, end));
         }
     }
-}
 
-impl InputManager {
-    /// Call once per frame if you want `scroll_delta`, `last_key`, and
-    /// `last_mouse_button` to represent only that frame.
-    pub fn reset_frame_deltas(&self) {
-        let mut inner = self.inner.lock();
-        inner.scroll_delta = (0.0, 0.0);
-        inner.last_key = None;
-        inner.last_mouse_button = None;
+    fn drag_start(&self, button: MouseButton) -> Option<PhysicalPosition<f64>> {
+        self.drags
+            .get(&button)
+            .copied()
+            .or_else(|| self.ended_drags.get(&button).map(|(start, _)| *start))
+    }
+
+    fn scroll_delta(&self) -> (f32, f32) {
+        let x = self.scroll_pixels.0 + self.scroll_lines.0 * self.scroll_line_height;
+        let y = self.scroll_pixels.1 + self.scroll_lines.1 * self.scroll_line_height;
+        if !self.shift_scroll_horizontal {
+            return (x, y);
+        }
+
+        let shifted = self.shift_scroll.0 + self.shift_scroll.1 * self.scroll_line_height;
+        (x + shifted, y - shifted)
+    }
+
+    fn drag_delta(&self, button: MouseButton) -> (f64, f64) {
+        let (start, end) = match self.drags.get(&button) {
+            Some(start) => (*start, self.cursor_position.unwrap_or(*start)),
+            None => match self.ended_drags.get(&button) {
+                Some(drag) => *drag,
+                None => return (0.0, 0.0),
+            },
+        };
+        (end.x - start.x, end.y - start.y)
+    }
+
+    /// Reads `id`'s name and battery from gilrs, on a (re)connection or the first time an
+    /// event mentions it. Whether it's connected goes by the event being applied rather than by
+    /// gilrs, which has already seen the whole backlog and so may know of a later disconnection.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn refresh_gamepad_info(&mut self, id: GamepadId, is_connected: bool) -> &mut GamepadState {
+        let gamepad = self.gilrs.get_mut().gamepad(id);
+        let state = self.gamepads.entry(id).or_default();
+        state.info.name = gamepad.name().to_string();
+        state.info.is_connected = is_connected;
+        state.info.power_info = gamepad.power_info().into();
+        state
+    }
+
+    /// The state of `id` for an input event, keeping its battery level current.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn gamepad_state(&mut self, id: GamepadId) -> &mut GamepadState {
+        if !self.gamepads.contains_key(&id) {
+            return self.refresh_gamepad_info(id, true);
+        }
+        let power_info = self.gilrs.get_mut().gamepad(id).power_info().into();
+        let state = self.gamepads.entry(id).or_default();
+        state.info.power_info = power_info;
+        state
+    }
+
+    /// Starts a new frame of gamepad deltas, remembering the analog values the frame ended on.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn reset_gamepad_frame(&mut self) {
+        self.gamepad_frame.just_pressed.clear();
+        self.gamepad_frame.just_released.clear();
+        self.gamepad_frame.split.reset();
+        self.gamepad_events.clear();
+        self.gamepad_frame.previous_values = self
+            .gamepads
+            .iter()
+            .flat_map(|(id, state)| {
+                state
+                    .button_values
+                    .iter()
+                    .map(move |(button, value)| ((*id, *button), *value))
+            })
+            .collect();
+        self.gamepad_frame.previous_axes = self
+            .gamepads
+            .iter()
+            .flat_map(|(id, state)| state.axes.iter().map(move |(axis, value)| ((*id, *axis), *value)))
+            .collect();
     }
 
-    /// Poll gamepad events (gilrs). Call once per frame.
+    /// Applies the gilrs events that came in since the last call, returning whether there
+    /// were any. Presses accumulate until the frame is reset, so pumping more than once a
+    /// frame loses nothing.
     ///
-    /// This is separate from `poll_window_event` because gamepads are not driven
-    /// by winit window events.
-    pub fn update_gamepads(&self) {
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            self.inner.lock().pump_gilrs_events();
+    /// When pumping was held up, say while the app was paused, a backlog of events comes in
+    /// at once. Rather than collapsing it into one frame, where a button pressed twice would
+    /// look like a single press, events are split across frames: a frame stops at an event
+    /// that would repeat an edge it already has for the same button, and after the
+    /// [InputManager::set_gamepad_event_limit] events. The rest wait, in order, for the next
+    /// frame. Connections and disconnections don't count towards the limit.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn pump_gilrs_events(&mut self) -> bool {
+        while let Some(ev) = self.gilrs.get_mut().next_event() {
+            self.gamepad_backlog.push_back(ev);
+        }
+
+        let pending = self.gamepad_events.len();
+        while let Some(ev) = self.gamepad_frame.split.next(&mut self.gamepad_backlog, self.gamepad_event_limit, |ev| {
+            (ev.id, SplitKind::of(&ev.event))
+        }) {
+            self.apply_gilrs_event(ev);
         }
+        // anything left over needs another frame to be seen.
+        self.gamepad_events.len() > pending || !self.gamepad_backlog.is_empty()
     }
 
-    /// Returns true if this `WindowEvent` is one we treat as user input.
-    pub fn is_input_event(event: &WindowEvent) -> bool {
-        matches!(
-            event,
-            WindowEvent::KeyboardInput { .. }
-                | WindowEvent::CursorMoved { .. }
-                | WindowEvent::MouseInput { .. }
-                | WindowEvent::MouseWheel { .. }
-                | WindowEvent::ModifiersChanged(_)
-        )
+    /// Lets the backlog keep draining while no frames are drawn, such as while the window is
+    /// minimised, by starting a new split of it. The presses and releases applied so far are
+    /// kept for the next frame drawn, and of the raw events only the newest
+    /// [InputManager::set_gamepad_event_limit] are.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn split_idle_gamepad_events(&mut self) {
+        self.gamepad_frame.split.reset();
+        if let Some(excess) = self.gamepad_event_limit.and_then(|limit| self.gamepad_events.len().checked_sub(limit)) {
+            self.gamepad_events.drain(..excess);
+        }
     }
 
-    pub(crate) fn poll(&self, event: WindowEvent) {
-        let mut inner = self.inner.lock();
-        match &event {
-            WindowEvent::KeyboardInput { event, .. } => {
-                if let PhysicalKey::Code(code) = event.physical_key {
-                    inner.last_key = Some((code, event.state));
-                    match event.state {
-                        ElementState::Pressed => {
-                            inner.keys_down.insert(code);
-                        }
-                        ElementState::Released => {
-                            inner.keys_down.remove(&code);
-                        }
-                    }
-                }
+    fn apply_gilrs_event(&mut self, ev: gilrs::Event) {
+        self.gamepad_events.push(ev);
+        let id = ev.id;
+        match ev.event {
+            EventType::Connected => {
+                let info = self.refresh_gamepad_info(id, true).info.clone();
+                self.players.assign(id);
+                self.gamepad_connections.push(GamepadConnection::Connected(id, info));
             }
-            WindowEvent::CursorMoved { position, .. } => {
-                inner.cursor_position = Some(*position);
+            EventType::Disconnected => {
+                self.refresh_gamepad_info(id, false);
+                self.players.release(id);
+                self.gamepad_pressed_at.retain(|(pad, _), _| *pad != id);
+                self.gamepad_connections.push(GamepadConnection::Disconnected(id));
             }
-            WindowEvent::MouseInput { state, button, .. } => {
-                inner.last_mouse_button = Some((*button, *state));
-                match state {
-                    ElementState::Pressed => {
-                        inner.mouse_buttons_down.insert(*button);
-                    }
-                    ElementState::Released => {
-                        inner.mouse_buttons_down.remove(button);
-                    }
-                }
+            EventType::ButtonPressed(button, _) => {
+                self.gamepad_state(id).buttons_down.insert(button);
+                self.gamepad_frame.just_pressed.insert((id, button));
+                // when it was pressed, not when a backlog got round to it.
+                self.gamepad_pressed_at.entry((id, button)).or_insert_with(|| event_instant(ev.time));
+            }
+            EventType::ButtonReleased(button, _) => {
+                self.gamepad_state(id).buttons_down.remove(&button);
+                self.gamepad_frame.just_released.insert((id, button));
+                self.gamepad_pressed_at.remove(&(id, button));
+            }
+            EventType::ButtonChanged(button, value, _) => {
+                self.gamepad_state(id).button_values.insert(button, value.clamp(0.0, 1.0));
+            }
+            EventType::AxisChanged(axis, value, _) => {
+                self.gamepad_state(id).axes.insert(axis, normalize_axis_value(value));
             }
-            WindowEvent::MouseWheel { delta, .. } => match delta {
-                MouseScrollDelta::LineDelta(x, y) => {
-                    inner.scroll_delta.0 += *x;
-                    inner.scroll_delta.1 += *y;
-                }
-                MouseScrollDelta::PixelDelta(pos) => {
-                    inner.scroll_delta.0 += pos.x as f32;
-                    inner.scroll_delta.1 += pos.y as f32;
-                }
-            },
             _ => {}
         }
-        inner.latest_event = Some(event);
+    }
+}
+
+/// `time`, a gilrs event's wall-clock timestamp, on the monotonic clock. Events stamped in the
+/// future by a clock change count as now.
+#[cfg(not(target_arch = "wasm32"))]
+fn event_instant(time: std::time::SystemTime) -> Instant {
+    let now = Instant::now();
+    let age = std::time::SystemTime::now().duration_since(time).unwrap_or_default();
+    now.checked_sub(age).unwrap_or(now)
+}
+
+/// A read-only view of the keyboard and mouse state, see [InputManager::with_snapshot].
+pub struct InputSnapshot<'a> {
+    inner: &'a InputInner,
+}
+
+impl InputSnapshot<'_> {
+    /// See [InputManager::set_captured].
+    pub fn is_captured(&self, kind: InputKind) -> bool {
+        self.inner.captured.contains(&kind)
     }
 
     pub fn is_key_down(&self, key: KeyCode) -> bool {
-        self.inner.lock().keys_down.contains(&key)
+        self.inner.keys_down.contains(&key)
+    }
+
+    /// Whether a key producing `key` under the current keyboard layout is held, such as "the
+    /// key labelled Z" whichever row it's on. Characters are matched as typed, so with Shift
+    /// held the `z` key is `Key::Character("Z")`. Use [Self::is_key_down] for controls that
+    /// should stay in the same place on every layout, like WASD.
+    pub fn is_logical_key_down(&self, key: &Key) -> bool {
+        self.inner.logical_keys_down.values().any(|held| held == key)
     }
 
     pub fn is_mouse_down(&self, button: MouseButton) -> bool {
-        self.inner.lock().mouse_buttons_down.contains(&button)
+        self.inner.mouse_buttons_down.contains(&button)
     }
 
-    pub fn cursor_position(&self) -> Option<PhysicalPosition<f64>> {
-        self.inner.lock().cursor_position
+    /// How long `key` has been held, or `None` if it isn't down. Auto-repeat doesn't restart
+    /// the clock.
+    pub fn key_held_for(&self, key: KeyCode) -> Option<Duration> {
+        self.inner.key_pressed_at.get(&key).map(Instant::elapsed)
     }
 
-    pub fn scroll_delta(&self) -> (f32, f32) {
-        self.inner.lock().scroll_delta
+    /// Like [Self::key_held_for], for mouse buttons.
+    pub fn mouse_held_for(&self, button: MouseButton) -> Option<Duration> {
+        self.inner.mouse_pressed_at.get(&button).map(Instant::elapsed)
     }
 
-    pub fn last_key(&self) -> Option<(KeyCode, ElementState)> {
-        self.inner.lock().last_key
+    pub fn keys_down(&self) -> &HashSet<KeyCode> {
+        &self.inner.keys_down
     }
 
-    pub fn last_mouse_button(&self) -> Option<(MouseButton, ElementState)> {
-        self.inner.lock().last_mouse_button
+    pub fn mouse_buttons_down(&self) -> &HashSet<MouseButton> {
+        &self.inner.mouse_buttons_down
     }
 
-    pub fn take_latest_event(&self) -> Option<WindowEvent> {
-        self.inner.lock().latest_event.take()
+    /// Keys that went down this frame, in the order they were pressed.
+    pub fn keys_just_pressed(&self) -> &[KeyCode] {
+        &self.inner.keys_just_pressed
     }
 
-    // --------------------
-    // Gamepad query helpers
-    // --------------------
+    /// Whether `key` went down this frame while exactly `modifiers` were held. Auto-repeat
+    /// doesn't count, so holding the keys down fires once.
+    pub fn was_shortcut_just_pressed(&self, modifiers: ModifiersState, key: KeyCode) -> bool {
+        self.inner.shortcuts_just_pressed.contains(&Shortcut::new(modifiers, key))
+    }
 
-    #[cfg(not(target_arch = "wasm32"))]
-    pub fn gamepads_snapshot(&self) -> GamepadsSnapshot {
-        let inner = self.inner.lock();
-        GamepadsSnapshot {
-            gamepads: inner.gamepads.clone(),
-        }
+    /// `None` until the cursor first moves over the window, and again once it leaves.
+    pub fn cursor_position(&self) -> Option<PhysicalPosition<f64>> {
+        self.inner.cursor_position
     }
 
-    #[cfg(target_arch = "wasm32")]
-    pub fn gamepads_snapshot(&self) -> GamepadsSnapshot {
-        GamepadsSnapshot {
-            gamepads: HashMap::new(),
-        }
+    pub fn cursor_in_window(&self) -> bool {
+        let size = self.inner.window_size;
+        self.inner.cursor_entered
+            && self.inner.cursor_position.is_none_or(|position| {
+                (0.0..size.width as f64).contains(&position.x) && (0.0..size.height as f64).contains(&position.y)
+            })
     }
 
-    #[cfg(not(target_arch = "wasm32"))]
-    pub fn is_button_pressed(&self, id: GamepadId, button: Button) -> bool {
+    pub fn cursor_position_logical(&self) -> Option<LogicalPosition<f64>> {
         self.inner
-            .lock()
+            .cursor_position
+            .map(|position| position.to_logical(self.inner.scale_factor))
+    }
+
+    pub fn cursor_position_normalized(&self) -> Option<(f32, f32)> {
+        let size = self.inner.window_size;
+        if size.width == 0 || size.height == 0 {
+            return None;
+        }
+
+        self.inner.cursor_position.map(|position| {
+            (
+                (position.x / size.width as f64) as f32,
+                (position.y / size.height as f64) as f32,
+            )
+        })
+    }
+
+    pub fn scroll_delta(&self) -> (f32, f32) {
+        self.inner.scroll_delta()
+    }
+
+    pub fn smoothed_scroll(&self) -> (f32, f32) {
+        self.inner.smoothed_scroll
+    }
+
+    pub fn scroll_pixels(&self) -> (f32, f32) {
+        self.inner.scroll_pixels
+    }
+
+    pub fn scroll_lines(&self) -> (f32, f32) {
+        self.inner.scroll_lines
+    }
+
+    pub fn mouse_motion(&self) -> (f64, f64) {
+        self.inner.mouse_motion
+    }
+
+    pub fn modifiers(&self) -> ModifiersState {
+        self.inner.modifiers
+    }
+
+    /// Whether either Ctrl key is held.
+    pub fn ctrl(&self) -> bool {
+        self.inner.modifiers.control_key()
+    }
+
+    /// Whether either Shift key is held.
+    pub fn shift(&self) -> bool {
+        self.inner.modifiers.shift_key()
+    }
+
+    /// Whether either Alt (Option on macOS) key is held.
+    pub fn alt(&self) -> bool {
+        self.inner.modifiers.alt_key()
+    }
+
+    /// Whether either Super key (Windows on Windows, Command on macOS) is held.
+    pub fn super_key(&self) -> bool {
+        self.inner.modifiers.super_key()
+    }
+
+    pub fn last_key(&self) -> Option<(KeyCode, ElementState)> {
+        self.inner.last_key
+    }
+
+    /// The text an IME is composing but hasn't committed yet, to show inline (usually
+    /// underlined) where it will end up. The range is the byte range of the IME's cursor or
+    /// selection within the text, if it reports one.
+    pub fn ime_preedit(&self) -> Option<(&str, Option<(usize, usize)>)> {
+        self.inner
+            .ime_preedit
+            .as_ref()
+            .map(|(text, cursor)| (text.as_str(), *cursor))
+    }
+
+    /// Text an IME committed this frame, which should be inserted like typed text. Keys that
+    /// go through an IME produce this instead of [Self::last_text].
+    pub fn ime_commit(&self) -> Option<&str> {
+        Some(self.inner.ime_commit.as_str()).filter(|text| !text.is_empty())
+    }
+
+    /// Text typed by the last key press this frame, including auto-repeats. Dead keys and IME
+    /// composition don't produce any here until they are committed.
+    pub fn last_text(&self) -> Option<&SmolStr> {
+        self.inner.last_text.as_ref()
+    }
+
+    pub fn last_mouse_button(&self) -> Option<(MouseButton, ElementState)> {
+        self.inner.last_mouse_button
+    }
+
+    pub fn was_double_click(&self, button: MouseButton) -> bool {
+        self.inner.double_clicks.contains(&button)
+    }
+
+    pub fn drag_start(&self, button: MouseButton) -> Option<PhysicalPosition<f64>> {
+        self.inner.drag_start(button)
+    }
+
+    pub fn drag_delta(&self, button: MouseButton) -> (f64, f64) {
+        self.inner.drag_delta(button)
+    }
+
+    pub fn drag_ended(&self, button: MouseButton) -> bool {
+        self.inner.ended_drags.contains_key(&button)
+    }
+}
+
+/// A manager for input.
+///
+/// Queries only take a read lock, so a scene reading input doesn't contend with other readers;
+/// the write lock is reserved for feeding events in and resetting per-frame state.
+pub struct InputManager {
+    inner: Arc<RwLock<InputInner>>,
+}
+
+impl Clone for InputManager {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl Default for InputManager {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(InputInner::new("", AxisFiltering::default()))),
+        }
+    }
+}
+
+impl InputManager {
+    /// An input manager whose gamepads also use the SDL `mappings`, see
+    /// [crate::AppBuilder::gamepad_mappings], with their axes filtered as `filtering` says.
+    pub(crate) fn with_gamepad_config(mappings: &str, filtering: AxisFiltering) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(InputInner::new(mappings, filtering))),
+        }
+    }
+
+    /// Call once per frame if you want `scroll_delta`, `mouse_motion`, `last_key`, `last_text`,
+    /// `ime_commit`, `last_mouse_button`, `drain_events` and the gamepad presses and releases to
+    /// represent only that frame.
+    pub fn reset_frame_deltas(&self) {
+        let mut inner = self.inner.write();
+        #[cfg(not(target_arch = "wasm32"))]
+        inner.reset_gamepad_frame();
+        inner.scroll_pixels = (0.0, 0.0);
+        inner.scroll_lines = (0.0, 0.0);
+        inner.shift_scroll = (0.0, 0.0);
+        inner.mouse_motion = (0.0, 0.0);
+        inner.events.clear();
+        inner.keys_just_pressed.clear();
+        inner.shortcuts_just_pressed.clear();
+        inner.last_key = None;
+        inner.last_text = None;
+        inner.ime_commit.clear();
+        inner.last_mouse_button = None;
+        inner.double_clicks.clear();
+        inner.ended_drags.clear();
+    }
+
+    /// Works out the frame's share of the smoothed scrolling, `dt` after the last frame. Called
+    /// by the app loop before `update`, once the frame's events are in.
+    ///
+    /// This is also where a frame is recorded or played back, see [InputRecording]. Returns
+    /// the `dt` the frame should run with, which during playback is the recorded one.
+    pub(crate) fn begin_frame(&self, dt: Duration) -> Duration {
+        let mut inner = self.inner.write();
+        let dt = match inner.replay.begin_frame(dt) {
+            Some(frame) => {
+                for event in &frame.events {
+                    inner.apply(event);
+                }
+                frame.dt
+            }
+            None => dt,
+        };
+
+        let (x, y) = inner.scroll_delta();
+        let remaining = (inner.scroll_remaining.0 + x, inner.scroll_remaining.1 + y);
+
+        let kept = if inner.scroll_half_life.is_zero() {
+            0.0
+        } else {
+            0.5f32.powf(dt.as_secs_f32() / inner.scroll_half_life.as_secs_f32())
+        };
+        // below a hundredth of a pixel there's nothing left to see, so finish it off.
+        let kept = |remaining: f32| if (remaining * kept).abs() < 0.01 { 0.0 } else { remaining * kept };
+        inner.scroll_remaining = (kept(remaining.0), kept(remaining.1));
+        inner.smoothed_scroll = (remaining.0 - inner.scroll_remaining.0, remaining.1 - inner.scroll_remaining.1);
+        dt
+    }
+
+    /// Starts recording every frame's keyboard and mouse input, along with its `dt`, from the
+    /// next frame. Start with nothing held, as keys and buttons that are already down when
+    /// recording starts won't be down when it's replayed.
+    pub fn start_recording(&self) {
+        self.inner.write().replay = Replay::Recording {
+            recording: InputRecording::default(),
+            pending: Vec::new(),
+        };
+    }
+
+    /// Stops recording, returning what was recorded. Empty if nothing was being recorded.
+    pub fn stop_recording(&self) -> InputRecording {
+        let mut inner = self.inner.write();
+        match std::mem::take(&mut inner.replay) {
+            Replay::Recording { recording, .. } => recording,
+            other => {
+                inner.replay = other;
+                InputRecording::default()
+            }
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        matches!(self.inner.read().replay, Replay::Recording { .. })
+    }
+
+    /// Plays `recording` back from the next frame on, stopping any recording. Until it ends or
+    /// [Self::stop_playback] is called, live keyboard and mouse input is ignored and every
+    /// frame runs with the recorded `dt`. Playback starts from a clean slate, as if the window
+    /// had just lost focus.
+    pub fn play(&self, recording: InputRecording) {
+        let mut inner = self.inner.write();
+        inner.apply(&RecordedEvent::Focused(false));
+        inner.cursor_position = None;
+        inner.cursor_entered = false;
+        inner.replay = Replay::Playing { recording, next_frame: 0 };
+    }
+
+    pub fn stop_playback(&self) {
+        let mut inner = self.inner.write();
+        if inner.replay.is_playing() {
+            inner.replay = Replay::Live;
+        }
+    }
+
+    pub fn is_playing_back(&self) -> bool {
+        self.inner.read().replay.is_playing()
+    }
+
+    /// Records the window's current scale factor and size, used by the logical and normalised
+    /// cursor accessors.
+    pub(crate) fn set_window_metrics(&self, scale_factor: f64, size: PhysicalSize<u32>) {
+        let mut inner = self.inner.write();
+        inner.scale_factor = scale_factor;
+        inner.window_size = size;
+    }
+
+    /// Poll gamepad events (gilrs). Call at least once per frame.
+    ///
+    /// This is separate from `poll_window_event` because gamepads are not driven
+    /// by winit window events. Calling it more often is fine: presses and releases add up
+    /// until [Self::reset_frame_deltas].
+    pub fn update_gamepads(&self) {
+        self.pump_gamepads();
+    }
+
+    /// Called instead of a frame reset when gamepads are pumped with no frame drawn since
+    /// the last pump, see [InputInner::split_idle_gamepad_events].
+    pub(crate) fn split_idle_gamepad_events(&self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        self.inner.write().split_idle_gamepad_events();
+    }
+
+    /// [Self::update_gamepads], returning whether any gamepad events came in.
+    pub(crate) fn pump_gamepads(&self) -> bool {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.inner.write().pump_gilrs_events()
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            false
+        }
+    }
+
+    /// Takes the gamepad connections and disconnections seen since the last call.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn take_gamepad_connections(&self) -> Vec<GamepadConnection> {
+        std::mem::take(&mut self.inner.write().gamepad_connections)
+    }
+
+    /// Returns true if this `WindowEvent` is one we treat as user input, which is what ends up
+    /// in [Self::drain_events]: everything the input manager tracks, plus input it leaves to
+    /// the scene, such as touches, touchpad gestures and pen pressure.
+    ///
+    /// What is tracked is decided by [RecordedEvent::from_window_event] alone, so an event
+    /// newly handled there is counted here without any changes.
+    pub fn is_input_event(event: &WindowEvent) -> bool {
+        matches!(
+            event,
+            WindowEvent::Touch(_)
+                | WindowEvent::PinchGesture { .. }
+                | WindowEvent::PanGesture { .. }
+                | WindowEvent::RotationGesture { .. }
+                | WindowEvent::DoubleTapGesture { .. }
+                | WindowEvent::TouchpadPressure { .. }
+                | WindowEvent::AxisMotion { .. }
+        ) || RecordedEvent::from_window_event(event).is_some()
+    }
+
+    /// Records the device events the input manager tracks, which is raw mouse motion.
+    pub(crate) fn poll_device_event(&self, event: &DeviceEvent) {
+        if let DeviceEvent::MouseMotion { delta } = event {
+            let mut inner = self.inner.write();
+            if !inner.replay.is_playing() {
+                let recorded = RecordedEvent::MouseMotion(delta.0, delta.1);
+                inner.replay.record(&recorded);
+                inner.apply(&recorded);
+            }
+        }
+    }
+
+    /// Feeds in a window event. Any event can be passed; the ones that aren't input, see
+    /// [Self::is_input_event], are ignored.
+    pub(crate) fn poll(&self, event: WindowEvent) {
+        let recorded = RecordedEvent::from_window_event(&event);
+        if recorded.is_none() && !Self::is_input_event(&event) {
+            return;
+        }
+
+        let mut inner = self.inner.write();
+        // a recording being played back stands in for live input.
+        if inner.replay.is_playing() {
+            return;
+        }
+
+        if let Some(recorded) = recorded {
+            inner.replay.record(&recorded);
+            inner.apply(&recorded);
+        }
+        inner.events.push(event.clone());
+        inner.latest_event = Some(event);
+    }
+
+    /// Locks the input state once and hands `f` a read-only view of it, so code that checks a
+    /// lot of input per frame doesn't take the lock for every query.
+    ///
+    /// Keep the closure short: events can't be fed in while it runs.
+    pub fn with_snapshot<R>(&self, f: impl FnOnce(&InputSnapshot<'_>) -> R) -> R {
+        let inner = self.inner.read();
+        f(&InputSnapshot { inner: &inner })
+    }
+
+    pub fn is_key_down(&self, key: KeyCode) -> bool {
+        self.inner.read().keys_down.contains(&key)
+    }
+
+    /// See [InputSnapshot::is_logical_key_down].
+    pub fn is_logical_key_down(&self, key: &Key) -> bool {
+        self.with_snapshot(|input| input.is_logical_key_down(key))
+    }
+
+    pub fn is_mouse_down(&self, button: MouseButton) -> bool {
+        self.inner.read().mouse_buttons_down.contains(&button)
+    }
+
+    /// See [InputSnapshot::key_held_for].
+    pub fn key_held_for(&self, key: KeyCode) -> Option<Duration> {
+        self.with_snapshot(|input| input.key_held_for(key))
+    }
+
+    /// See [InputSnapshot::mouse_held_for].
+    pub fn mouse_held_for(&self, button: MouseButton) -> Option<Duration> {
+        self.with_snapshot(|input| input.mouse_held_for(button))
+    }
+
+    /// Every key currently held down, in no particular order.
+    pub fn pressed_keys(&self) -> Vec<KeyCode> {
+        self.inner.read().keys_down.iter().copied().collect()
+    }
+
+    /// Every mouse button currently held down, in no particular order.
+    pub fn pressed_mouse_buttons(&self) -> Vec<MouseButton> {
+        self.inner.read().mouse_buttons_down.iter().copied().collect()
+    }
+
+    /// The first key that went down this frame, for "press any key to bind" flows. Held keys
+    /// repeating don't count.
+    pub fn any_key_just_pressed(&self) -> Option<KeyCode> {
+        self.inner.read().keys_just_pressed.first().copied()
+    }
+
+    /// See [InputSnapshot::was_shortcut_just_pressed].
+    pub fn was_shortcut_just_pressed(&self, modifiers: ModifiersState, key: KeyCode) -> bool {
+        self.with_snapshot(|input| input.was_shortcut_just_pressed(modifiers, key))
+    }
+
+    /// The cursor position in physical pixels, for pixel-exact work.
+    pub fn cursor_position(&self) -> Option<PhysicalPosition<f64>> {
+        self.inner.read().cursor_position
+    }
+
+    /// Whether the cursor is over the window (or the viewport panel, see
+    /// [crate::prelude::AppBehaviour::viewport_rect]), for hover states. While a button is
+    /// held, [Self::cursor_position] keeps following the cursor outside, which this tells
+    /// apart.
+    pub fn cursor_in_window(&self) -> bool {
+        self.with_snapshot(|input| input.cursor_in_window())
+    }
+
+    /// The cursor position in logical (scale-factor independent) coordinates, which is what UI
+    /// hit-testing should use.
+    pub fn cursor_position_logical(&self) -> Option<LogicalPosition<f64>> {
+        self.with_snapshot(|input| input.cursor_position_logical())
+    }
+
+    /// The cursor position mapped to 0..1 across the window, with (0, 0) at the top left.
+    pub fn cursor_position_normalized(&self) -> Option<(f32, f32)> {
+        self.with_snapshot(|input| input.cursor_position_normalized())
+    }
+
+    /// All scrolling this frame in pixels, with line-based scroll converted using
+    /// [Self::scroll_line_height]. Use this when you don't care where the scroll came from.
+    ///
+    /// With [Self::set_shift_scroll_horizontal] on, vertical scrolling done while holding
+    /// shift counts as horizontal here.
+    pub fn scroll_delta(&self) -> (f32, f32) {
+        self.with_snapshot(|input| input.scroll_delta())
+    }
+
+    /// [Self::scroll_delta] eased out over the next few frames, for zooming and panning that
+    /// glides rather than jumps with every wheel notch. Each frame hands out part of the
+    /// scrolling still to go, so over time it adds up to the same total as [Self::scroll_delta].
+    /// See [Self::set_scroll_half_life] for how quickly.
+    pub fn smoothed_scroll(&self) -> (f32, f32) {
+        self.with_snapshot(|input| input.smoothed_scroll())
+    }
+
+    /// Sets how long [Self::smoothed_scroll] takes to cover half of the scrolling still to go,
+    /// 50ms by default. Longer feels heavier and glides on for longer; zero turns the smoothing
+    /// off, so it matches [Self::scroll_delta].
+    pub fn set_scroll_half_life(&self, half_life: Duration) {
+        self.inner.write().scroll_half_life = half_life;
+    }
+
+    pub fn scroll_half_life(&self) -> Duration {
+        self.inner.read().scroll_half_life
+    }
+
+    /// Pixel-precise scrolling this frame, as produced by trackpads and high resolution wheels.
+    /// The axes are as the device reported them, without any shift remapping.
+    pub fn scroll_pixels(&self) -> (f32, f32) {
+        self.inner.read().scroll_pixels
+    }
+
+    /// Line-based scrolling this frame, as produced by notched mouse wheels. Each notch is
+    /// usually one line, which suits stepped zoom better than a pixel amount. Like
+    /// [Self::scroll_pixels], the axes are left as they came.
+    pub fn scroll_lines(&self) -> (f32, f32) {
+        self.inner.read().scroll_lines
+    }
+
+    /// Raw mouse movement this frame, in device units rather than pixels, straight from the
+    /// mouse without pointer acceleration. It keeps coming when the cursor is pinned against
+    /// the edge of the screen or grabbed, which is what first-person camera controls want.
+    ///
+    /// Desktop platforms and the web report it, most of them only while the window is focused.
+    pub fn mouse_motion(&self) -> (f64, f64) {
+        self.inner.read().mouse_motion
+    }
+
+    /// Sets how many pixels one line of scroll counts as in [Self::scroll_delta].
+    pub fn set_scroll_line_height(&self, pixels: f32) {
+        self.inner.write().scroll_line_height = pixels;
+    }
+
+    pub fn scroll_line_height(&self) -> f32 {
+        self.inner.read().scroll_line_height
+    }
+
+    /// Makes [Self::scroll_delta] treat shift + vertical scroll as horizontal, for mice that
+    /// only have a vertical wheel. Off by default. Where the platform already does this itself
+    /// (macOS), the scroll arrives horizontal and is left alone.
+    pub fn set_shift_scroll_horizontal(&self, enabled: bool) {
+        self.inner.write().shift_scroll_horizontal = enabled;
+    }
+
+    pub fn shift_scroll_horizontal(&self) -> bool {
+        self.inner.read().shift_scroll_horizontal
+    }
+
+    /// Modifier keys currently held, without telling left from right. Use this rather than
+    /// [Self::is_key_down] for shortcuts like Ctrl+S.
+    pub fn modifiers(&self) -> ModifiersState {
+        self.inner.read().modifiers
+    }
+
+    /// See [InputSnapshot::ctrl].
+    pub fn ctrl(&self) -> bool {
+        self.modifiers().control_key()
+    }
+
+    /// See [InputSnapshot::shift].
+    pub fn shift(&self) -> bool {
+        self.modifiers().shift_key()
+    }
+
+    /// See [InputSnapshot::alt].
+    pub fn alt(&self) -> bool {
+        self.modifiers().alt_key()
+    }
+
+    /// See [InputSnapshot::super_key].
+    pub fn super_key(&self) -> bool {
+        self.modifiers().super_key()
+    }
+
+    pub fn last_key(&self) -> Option<(KeyCode, ElementState)> {
+        self.inner.read().last_key
+    }
+
+    /// See [InputSnapshot::ime_preedit].
+    pub fn ime_preedit(&self) -> Option<(String, Option<(usize, usize)>)> {
+        self.inner.read().ime_preedit.clone()
+    }
+
+    /// See [InputSnapshot::ime_commit].
+    pub fn ime_commit(&self) -> Option<String> {
+        self.with_snapshot(|input| input.ime_commit().map(str::to_owned))
+    }
+
+    /// See [InputSnapshot::last_text].
+    pub fn last_text(&self) -> Option<SmolStr> {
+        self.inner.read().last_text.clone()
+    }
+
+    pub fn last_mouse_button(&self) -> Option<(MouseButton, ElementState)> {
+        self.inner.read().last_mouse_button
+    }
+
+    /// True if `button` was released this frame as the second click of a double-click. Clicks
+    /// pair up, so a triple-click reports one double-click followed by a fresh single click.
+    pub fn was_double_click(&self, button: MouseButton) -> bool {
+        self.inner.read().double_clicks.contains(&button)
+    }
+
+    /// Sets the longest gap between two clicks that still counts as a double-click. Defaults to
+    /// 400ms.
+    pub fn set_double_click_time(&self, time: Duration) {
+        self.inner.write().double_click_time = time;
+    }
+
+    pub fn double_click_time(&self) -> Duration {
+        self.inner.read().double_click_time
+    }
+
+    /// Sets how far, in physical pixels, the cursor may move between the two clicks of a
+    /// double-click. Defaults to 4.
+    pub fn set_double_click_distance(&self, pixels: f64) {
+        self.inner.write().double_click_distance = pixels;
+    }
+
+    pub fn double_click_distance(&self) -> f64 {
+        self.inner.read().double_click_distance
+    }
+
+    /// Sets how far (0..1) a stick has to be pushed before [Self::stick_direction] reports a
+    /// direction. Defaults to 0.5.
+    pub fn set_direction_deadzone(&self, deadzone: f32) {
+        self.inner.write().direction_deadzone = deadzone;
+    }
+
+    pub fn direction_deadzone(&self) -> f32 {
+        self.inner.read().direction_deadzone
+    }
+
+    /// Marks `kind` as claimed by the UI, or hands it back to the scene, so gameplay doesn't
+    /// react to keys typed into a text box or clicks on a panel drawn over the viewport.
+    ///
+    /// Capturing doesn't stop input being tracked. The plain queries such as [Self::is_key_down]
+    /// carry on reporting it, for the UI's own use and for shortcuts that should work anywhere;
+    /// their `_uncaptured` variants, such as [Self::is_key_down_uncaptured], report `kind` as idle
+    /// while it's captured, and are what gameplay code should read. A capture lasts until it's
+    /// set again, so the UI should refresh it every frame from its focus:
+    ///
+    /// - With egui, call this at the end of [crate::prelude::AppBehaviour::ui], with
+    ///   `egui.wants_keyboard_input()` and `egui.wants_pointer_input()`.
+    /// - With Slint, as in the editor, expose whether a text field has focus as an `out`
+    ///   property (its `has-focus`) and pass it for [InputKind::Keyboard] at the start of
+    ///   `update`. Slint already keeps pointer events over its own widgets from reaching the
+    ///   scene when a [crate::prelude::ViewportRect] is set, so only the keyboard needs this.
+    pub fn set_captured(&self, kind: InputKind, captured: bool) {
+        let mut inner = self.inner.write();
+        if captured {
+            inner.captured.insert(kind);
+        } else {
+            inner.captured.remove(&kind);
+        }
+    }
+
+    /// Whether the UI has claimed `kind`, see [Self::set_captured].
+    pub fn is_captured(&self, kind: InputKind) -> bool {
+        self.inner.read().captured.contains(&kind)
+    }
+
+    /// [Self::is_key_down], but never while the keyboard is captured.
+    pub fn is_key_down_uncaptured(&self, key: KeyCode) -> bool {
+        self.with_snapshot(|input| !input.is_captured(InputKind::Keyboard) && input.is_key_down(key))
+    }
+
+    /// [Self::is_logical_key_down], but never while the keyboard is captured.
+    pub fn is_logical_key_down_uncaptured(&self, key: &Key) -> bool {
+        self.with_snapshot(|input| !input.is_captured(InputKind::Keyboard) && input.is_logical_key_down(key))
+    }
+
+    /// [Self::any_key_just_pressed], but `None` while the keyboard is captured.
+    pub fn any_key_just_pressed_uncaptured(&self) -> Option<KeyCode> {
+        self.with_snapshot(|input| input.keys_just_pressed().first().copied().filter(|_| !input.is_captured(InputKind::Keyboard)))
+    }
+
+    /// [Self::was_shortcut_just_pressed], but never while the keyboard is captured.
+    pub fn was_shortcut_just_pressed_uncaptured(&self, modifiers: ModifiersState, key: KeyCode) -> bool {
+        self.with_snapshot(|input| !input.is_captured(InputKind::Keyboard) && input.was_shortcut_just_pressed(modifiers, key))
+    }
+
+    /// [Self::is_mouse_down], but never while the pointer is captured.
+    pub fn is_mouse_down_uncaptured(&self, button: MouseButton) -> bool {
+        self.with_snapshot(|input| !input.is_captured(InputKind::Pointer) && input.is_mouse_down(button))
+    }
+
+    /// [Self::scroll_delta], but nothing while the pointer is captured.
+    pub fn scroll_delta_uncaptured(&self) -> (f32, f32) {
+        self.with_snapshot(|input| if input.is_captured(InputKind::Pointer) { (0.0, 0.0) } else { input.scroll_delta() })
+    }
+
+    /// [Self::mouse_motion], but nothing while the pointer is captured.
+    pub fn mouse_motion_uncaptured(&self) -> (f64, f64) {
+        self.with_snapshot(|input| if input.is_captured(InputKind::Pointer) { (0.0, 0.0) } else { input.mouse_motion() })
+    }
+
+    /// Where the cursor was when `button` went down, while it is held and on the frame it is
+    /// released.
+    pub fn drag_start(&self, button: MouseButton) -> Option<PhysicalPosition<f64>> {
+        self.inner.read().drag_start(button)
+    }
+
+    /// How far the cursor has moved, in physical pixels, since `button` went down. On the frame
+    /// the drag ends this is the final offset; otherwise it is zero when nothing is dragging.
+    pub fn drag_delta(&self, button: MouseButton) -> (f64, f64) {
+        self.inner.read().drag_delta(button)
+    }
+
+    /// True on the frame a drag with `button` finished, either by releasing it or by the window
+    /// losing focus mid-drag.
+    pub fn drag_ended(&self, button: MouseButton) -> bool {
+        self.inner.read().ended_drags.contains_key(&button)
+    }
+
+    /// Takes the most recent input event. This is lossy: when several events came in since the
+    /// last call, all but the newest are gone, so typing and fast clicks should go through
+    /// [Self::drain_events] instead.
+    pub fn take_latest_event(&self) -> Option<WindowEvent> {
+        self.inner.write().latest_event.take()
+    }
+
+    /// Takes every input event from this frame, oldest first. Events not drained are dropped
+    /// when the frame ends.
+    pub fn drain_events(&self) -> Vec<WindowEvent> {
+        std::mem::take(&mut self.inner.write().events)
+    }
+
+    // --------------------
+    // Gamepad query helpers
+    // --------------------
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn gamepads_snapshot(&self) -> GamepadsSnapshot {
+        let inner = self.inner.read();
+        GamepadsSnapshot {
+            gamepads: inner.gamepads.clone(),
+            players: inner.players.clone(),
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn gamepads_snapshot(&self) -> GamepadsSnapshot {
+        GamepadsSnapshot {
+            gamepads: HashMap::new(),
+            players: PlayerSlots::default(),
+        }
+    }
+
+    /// Takes the raw gilrs events from this frame, including the ones lyrebird doesn't map onto
+    /// [GamepadState]. Events not drained are dropped when the next frame's are pumped. While
+    /// no frames are drawn only the newest [Self::set_gamepad_event_limit] are kept.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn drain_gamepad_events(&self) -> Vec<gilrs::Event> {
+        std::mem::take(&mut self.inner.write().gamepad_events)
+    }
+
+    /// Sets how many gilrs events a single frame applies, or `None` for no limit. Defaults to
+    /// 256. Whatever is over the limit is applied, in order, on the following frames, so a
+    /// burst after the app was paused plays out over several frames instead of one.
+    ///
+    /// A frame also never applies two presses, or two releases, of the same button: the second
+    /// waits for the next frame, limit or not, so each one shows up in
+    /// [Self::was_button_just_pressed].
+    ///
+    /// Connections and disconnections don't count towards the limit. While no frames are drawn,
+    /// such as while the window is minimised, the backlog keeps being applied, so
+    /// [GamepadInfo::is_connected] and the buttons held stay current; the presses from that
+    /// time all show up on the next frame drawn.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_gamepad_event_limit(&self, limit: Option<usize>) {
+        self.inner.write().gamepad_event_limit = limit;
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn gamepad_event_limit(&self) -> Option<usize> {
+        self.inner.read().gamepad_event_limit
+    }
+
+    /// How many gilrs events are waiting for a later frame, see [Self::set_gamepad_event_limit].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn gamepad_backlog_len(&self) -> usize {
+        self.inner.read().gamepad_backlog.len()
+    }
+
+    /// The player slot `id` occupies, see [PlayerSlots].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn slot_for(&self, id: GamepadId) -> Option<usize> {
+        self.inner.read().players.slot_for(id)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn slot_for(&self, _id: GamepadId) -> Option<usize> {
+        None
+    }
+
+    /// The gamepad playing as player `slot`, if one is connected.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn gamepad_for_slot(&self, slot: usize) -> Option<GamepadId> {
+        self.inner.read().players.gamepad_for_slot(slot)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn gamepad_for_slot(&self, _slot: usize) -> Option<GamepadId> {
+        None
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn is_button_pressed(&self, id: GamepadId, button: Button) -> bool {
+        self.inner
+            .read()
             .gamepads
             .get(&id)
             .is_some_and(|g| g.buttons_down.contains(&button))
@@ -347,10 +1790,21 @@ impl InputManager {
         false
     }
 
+    /// How long a gamepad button has been held, or `None` if it isn't down.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn button_held_for(&self, id: GamepadId, button: Button) -> Option<Duration> {
+        self.inner.read().gamepad_pressed_at.get(&(id, button)).map(Instant::elapsed)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn button_held_for(&self, _id: GamepadId, _button: Button) -> Option<Duration> {
+        None
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     pub fn button_value(&self, id: GamepadId, button: Button) -> f32 {
         self.inner
-            .lock()
+            .read()
             .gamepads
             .get(&id)
             .and_then(|g| g.button_values.get(&button).copied())
@@ -365,7 +1819,7 @@ impl InputManager {
     #[cfg(not(target_arch = "wasm32"))]
     pub fn axis_value(&self, id: GamepadId, axis: Axis) -> f32 {
         self.inner
-            .lock()
+            .read()
             .gamepads
             .get(&id)
             .and_then(|g| g.axes.get(&axis).copied())
@@ -377,10 +1831,25 @@ impl InputManager {
         0.0
     }
 
+    /// The deadzone gilrs knows for `axis` of gamepad `id`, from the hardware or the OS, or
+    /// `None` if there isn't one. With [AxisFiltering::Raw] this is the place to start from
+    /// when applying a deadzone of your own.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn axis_deadzone(&self, id: GamepadId, axis: Axis) -> Option<f32> {
+        let mut inner = self.inner.write();
+        let gamepad = inner.gilrs.get_mut().connected_gamepad(id)?;
+        gamepad.deadzone(gamepad.axis_code(axis)?)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn axis_deadzone(&self, _id: GamepadId, _axis: Axis) -> Option<f32> {
+        None
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     pub fn was_button_just_pressed(&self, id: GamepadId, button: Button) -> bool {
         self.inner
-            .lock()
+            .read()
             .gamepad_frame
             .just_pressed
             .contains(&(id, button))
@@ -394,7 +1863,7 @@ impl InputManager {
     #[cfg(not(target_arch = "wasm32"))]
     pub fn was_button_just_released(&self, id: GamepadId, button: Button) -> bool {
         self.inner
-            .lock()
+            .read()
             .gamepad_frame
             .just_released
             .contains(&(id, button))
@@ -404,4 +1873,383 @@ impl InputManager {
     pub fn was_button_just_released(&self, _id: GamepadId, _button: Button) -> bool {
         false
     }
-}
\ No newline at end of file
+
+    /// Treats an analog button, usually a trigger such as [Button::RightTrigger2], as held once
+    /// it is pulled to at least `threshold` (0..1).
+    pub fn is_trigger_down(&self, id: GamepadId, button: Button, threshold: f32) -> bool {
+        self.button_value(id, button) >= threshold
+    }
+
+    /// True on the frame an analog button crossed `threshold` on the way down.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn was_trigger_just_pressed(&self, id: GamepadId, button: Button, threshold: f32) -> bool {
+        let (previous, current) = self.trigger_values(id, button);
+        previous < threshold && current >= threshold
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn was_trigger_just_pressed(&self, _id: GamepadId, _button: Button, _threshold: f32) -> bool {
+        false
+    }
+
+    /// True on the frame an analog button dropped back below `threshold`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn was_trigger_just_released(&self, id: GamepadId, button: Button, threshold: f32) -> bool {
+        let (previous, current) = self.trigger_values(id, button);
+        previous >= threshold && current < threshold
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn was_trigger_just_released(&self, _id: GamepadId, _button: Button, _threshold: f32) -> bool {
+        false
+    }
+
+    /// Which of the eight directions `stick` is pushed in, or `None` while it is within the
+    /// [Self::direction_deadzone].
+    pub fn stick_direction(&self, id: GamepadId, stick: Stick) -> Option<Direction> {
+        let (x, y) = self.stick_values(id, stick).1;
+        Direction::from_axes(x, y, self.direction_deadzone())
+    }
+
+    /// The direction `stick` was pushed into this frame, having pointed elsewhere (or nowhere)
+    /// the frame before. This fires once per push, like a button press, which is what menu
+    /// navigation wants.
+    pub fn stick_direction_just_pressed(&self, id: GamepadId, stick: Stick) -> Option<Direction> {
+        let ((px, py), (x, y)) = self.stick_values(id, stick);
+        let deadzone = self.direction_deadzone();
+        Direction::from_axes(x, y, deadzone)
+            .filter(|&direction| Direction::from_axes(px, py, deadzone) != Some(direction))
+    }
+
+    /// The direction the D-pad is held in, with two neighbouring buttons making a diagonal.
+    /// Gamepads that report their D-pad as axes instead of buttons work too.
+    pub fn dpad_direction(&self, id: GamepadId) -> Option<Direction> {
+        let (x, y) = self.dpad_values(id).1;
+        Direction::from_axes(x, y, 0.5)
+    }
+
+    /// Like [Self::stick_direction_just_pressed], for the D-pad.
+    pub fn dpad_direction_just_pressed(&self, id: GamepadId) -> Option<Direction> {
+        let ((px, py), (x, y)) = self.dpad_values(id);
+        Direction::from_axes(x, y, 0.5).filter(|&direction| Direction::from_axes(px, py, 0.5) != Some(direction))
+    }
+
+    /// Where `stick` was at the end of the previous frame, and where it is now.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn stick_values(&self, id: GamepadId, stick: Stick) -> ((f32, f32), (f32, f32)) {
+        let inner = self.inner.read();
+        let (x, y) = stick.axes();
+        let previous = |axis| inner.gamepad_frame.previous_axes.get(&(id, axis)).copied().unwrap_or(0.0);
+        let current = |axis| {
+            inner
+                .gamepads
+                .get(&id)
+                .and_then(|g| g.axes.get(&axis).copied())
+                .unwrap_or(0.0)
+        };
+        ((previous(x), previous(y)), (current(x), current(y)))
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn stick_values(&self, _id: GamepadId, _stick: Stick) -> ((f32, f32), (f32, f32)) {
+        ((0.0, 0.0), (0.0, 0.0))
+    }
+
+    /// The D-pad as a pair of axes at the end of the previous frame, and now, from its buttons
+    /// or, when none of those are held, its axes.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn dpad_values(&self, id: GamepadId) -> ((f32, f32), (f32, f32)) {
+        let inner = self.inner.read();
+        let frame = &inner.gamepad_frame;
+        let state = inner.gamepads.get(&id);
+        let down = |button| state.is_some_and(|g| g.buttons_down.contains(&button));
+        let was_down = |button| {
+            (down(button) && !frame.just_pressed.contains(&(id, button))) || frame.just_released.contains(&(id, button))
+        };
+        let from_buttons = |held: &dyn Fn(Button) -> bool| {
+            let axis = |positive, negative| held(positive) as i32 as f32 - held(negative) as i32 as f32;
+            (axis(Button::DPadRight, Button::DPadLeft), axis(Button::DPadUp, Button::DPadDown))
+        };
+        let or_axes = |buttons: (f32, f32), axis: &dyn Fn(Axis) -> f32| {
+            if buttons == (0.0, 0.0) {
+                (axis(Axis::DPadX), axis(Axis::DPadY))
+            } else {
+                buttons
+            }
+        };
+
+        let previous = or_axes(from_buttons(&was_down), &|axis| {
+            frame.previous_axes.get(&(id, axis)).copied().unwrap_or(0.0)
+        });
+        let current = or_axes(from_buttons(&down), &|axis| {
+            state.and_then(|g| g.axes.get(&axis).copied()).unwrap_or(0.0)
+        });
+        (previous, current)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn dpad_values(&self, _id: GamepadId) -> ((f32, f32), (f32, f32)) {
+        ((0.0, 0.0), (0.0, 0.0))
+    }
+
+    /// The value of an analog button at the end of the previous frame, and now.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn trigger_values(&self, id: GamepadId, button: Button) -> (f32, f32) {
+        let inner = self.inner.read();
+        let previous = inner
+            .gamepad_frame
+            .previous_values
+            .get(&(id, button))
+            .copied()
+            .unwrap_or(0.0);
+        let current = inner
+            .gamepads
+            .get(&id)
+            .and_then(|g| g.button_values.get(&button).copied())
+            .unwrap_or(0.0);
+        (previous, current)
+    }
+}
+
+/// Narrows window events down to the ones meant for a scene shown in part of the window, see
+/// [crate::prelude::AppBehaviour::viewport_rect].
+#[derive(Default)]
+pub(crate) struct ViewportInput {
+    hovered: bool,
+    /// Buttons pressed over the viewport, which keep the cursor routed to the scene until they
+    /// are released, so dragging out of the viewport doesn't cut the drag short.
+    captured: HashSet<MouseButton>,
+}
+
+impl ViewportInput {
+    /// Returns `event` as the scene should see it, or `None` if it belongs to the UI around the
+    /// viewport. `bounds` is the viewport's top-left corner and size in physical pixels, or
+    /// `None` when it fills the window.
+    pub(crate) fn route(
+        &mut self,
+        bounds: Option<(PhysicalPosition<f64>, PhysicalSize<u32>)>,
+        event: &WindowEvent,
+    ) -> Option<WindowEvent> {
+        let Some((origin, size)) = bounds else {
+            return Some(event.clone());
+        };
+
+        match event {
+            WindowEvent::CursorMoved { device_id, position } => {
+                let local = PhysicalPosition::new(position.x - origin.x, position.y - origin.y);
+                let inside = (0.0..size.width as f64).contains(&local.x) && (0.0..size.height as f64).contains(&local.y);
+                let was_hovered = std::mem::replace(&mut self.hovered, inside);
+
+                if inside || !self.captured.is_empty() {
+                    Some(WindowEvent::CursorMoved {
+                        device_id: *device_id,
+                        position: local,
+                    })
+                } else if was_hovered {
+                    Some(WindowEvent::CursorLeft { device_id: *device_id })
+                } else {
+                    None
+                }
+            }
+            WindowEvent::CursorLeft { .. } => {
+                self.hovered = false;
+                Some(event.clone())
+            }
+            // the first move over the viewport stands in for this.
+            WindowEvent::CursorEntered { .. } => None,
+            WindowEvent::MouseInput { state, button, .. } => match state {
+                ElementState::Pressed if self.hovered => {
+                    self.captured.insert(*button);
+                    Some(event.clone())
+                }
+                ElementState::Pressed => None,
+                ElementState::Released => {
+                    self.captured.remove(button);
+                    Some(event.clone())
+                }
+            },
+            WindowEvent::KeyboardInput { event: key, .. } => {
+                (self.hovered || key.state == ElementState::Released).then(|| event.clone())
+            }
+            WindowEvent::MouseWheel { .. }
+            | WindowEvent::Touch(_)
+            | WindowEvent::PinchGesture { .. }
+            | WindowEvent::PanGesture { .. }
+            | WindowEvent::RotationGesture { .. }
+            | WindowEvent::DoubleTapGesture { .. } => self.hovered.then(|| event.clone()),
+            WindowEvent::Focused(false) => {
+                self.captured.clear();
+                Some(event.clone())
+            }
+            _ => Some(event.clone()),
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use std::collections::VecDeque;
+
+    use gilrs::Button;
+
+    use super::{FrameSplit, SplitKind};
+    #[cfg(target_os = "linux")]
+    use super::InputManager;
+
+    /// Splits `backlog` into the frames it would take, resetting between them like a frame does.
+    fn frames(backlog: &[(u32, SplitKind)], limit: Option<usize>) -> Vec<Vec<(u32, SplitKind)>> {
+        let mut backlog: VecDeque<_> = backlog.iter().copied().collect();
+        let mut split = FrameSplit::default();
+        let mut frames = Vec::new();
+        while !backlog.is_empty() {
+            let mut frame = Vec::new();
+            while let Some(event) = split.next(&mut backlog, limit, |&event| event) {
+                frame.push(event);
+            }
+            frames.push(frame);
+            split.reset();
+        }
+        frames
+    }
+
+    #[test]
+    fn double_press_spans_two_frames() {
+        use SplitKind::*;
+
+        let backlog = [(0, Pressed(Button::South)), (0, Released(Button::South)), (0, Pressed(Button::South)), (0, Released(Button::South))];
+        assert_eq!(frames(&backlog, None), [&backlog[..2], &backlog[2..]]);
+
+        // another gamepad's press of the same button doesn't repeat anything.
+        let backlog = [(0, Pressed(Button::South)), (1, Pressed(Button::South))];
+        assert_eq!(frames(&backlog, None), [&backlog[..]]);
+    }
+
+    #[test]
+    fn limit_splits_frames_in_order() {
+        let backlog: Vec<_> = (0..10).map(|id| (id, SplitKind::Other)).collect();
+        let frames = frames(&backlog, Some(4));
+        assert_eq!(frames.iter().map(Vec::len).collect::<Vec<_>>(), [4, 4, 2]);
+        assert_eq!(frames.concat(), backlog);
+    }
+
+    #[test]
+    fn connections_dont_count_towards_the_limit() {
+        use SplitKind::*;
+
+        let mut backlog = VecDeque::from([(0, Other), (1, Connection), (0, Pressed(Button::South)), (0, Pressed(Button::South)), (1, Connection)]);
+        let mut split = FrameSplit::default();
+        let mut frame = Vec::new();
+        while let Some(event) = split.next(&mut backlog, Some(1), |&event| event) {
+            frame.push(event);
+        }
+        // the limit of one is reached by the first event, but the connection behind it still fits.
+        assert_eq!(frame, [(0, Other), (1, Connection)]);
+        assert_eq!(backlog.len(), 3);
+    }
+
+    /// A gamepad made up through uinput, which goes away again when dropped.
+    #[cfg(target_os = "linux")]
+    struct VirtualGamepad(std::os::fd::OwnedFd);
+
+    #[cfg(target_os = "linux")]
+    impl VirtualGamepad {
+        const UI_DEV_CREATE: libc::Ioctl = libc::_IO(b'U' as u32, 1);
+        const UI_DEV_DESTROY: libc::Ioctl = libc::_IO(b'U' as u32, 2);
+        const UI_DEV_SETUP: libc::Ioctl = libc::_IOW::<libc::uinput_setup>(b'U' as u32, 3);
+        const UI_ABS_SETUP: libc::Ioctl = libc::_IOW::<libc::uinput_abs_setup>(b'U' as u32, 4);
+        const UI_SET_EVBIT: libc::Ioctl = libc::_IOW::<libc::c_int>(b'U' as u32, 100);
+        const UI_SET_KEYBIT: libc::Ioctl = libc::_IOW::<libc::c_int>(b'U' as u32, 101);
+        const UI_SET_ABSBIT: libc::Ioctl = libc::_IOW::<libc::c_int>(b'U' as u32, 103);
+
+        const EV_KEY: libc::c_int = 0x01;
+        const EV_ABS: libc::c_int = 0x03;
+        const BUTTONS: [libc::c_int; 4] = [0x130, 0x131, 0x133, 0x13b]; // south, east, north, start
+        const AXES: [u16; 2] = [0x00, 0x01]; // x, y
+        const BUS_USB: u16 = 0x03;
+
+        /// Creates a gamepad with a few buttons and a stick, enough for udev to call it a joystick.
+        fn create(name: &str) -> std::io::Result<Self> {
+            use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+            let check = |result: libc::c_int| if result < 0 { Err(std::io::Error::last_os_error()) } else { Ok(result) };
+
+            // SAFETY: the path is a valid C string, and the descriptor is owned from here on.
+            let fd = check(unsafe { libc::open(c"/dev/uinput".as_ptr(), libc::O_WRONLY | libc::O_NONBLOCK) })?;
+            let pad = Self(unsafe { OwnedFd::from_raw_fd(fd) });
+            let fd = pad.0.as_raw_fd();
+
+            let mut setup: libc::uinput_setup = unsafe { std::mem::zeroed() };
+            setup.id = libc::input_id { bustype: Self::BUS_USB, vendor: 0x1209, product: 0x0001, version: 1 };
+            for (to, from) in setup.name.iter_mut().zip(name.bytes().take(libc::UINPUT_MAX_NAME_SIZE - 1)) {
+                *to = from as libc::c_char;
+            }
+
+            // SAFETY: every request gets the argument type uinput expects of it.
+            unsafe {
+                check(libc::ioctl(fd, Self::UI_SET_EVBIT, Self::EV_KEY))?;
+                for button in Self::BUTTONS {
+                    check(libc::ioctl(fd, Self::UI_SET_KEYBIT, button))?;
+                }
+                check(libc::ioctl(fd, Self::UI_SET_EVBIT, Self::EV_ABS))?;
+                for axis in Self::AXES {
+                    check(libc::ioctl(fd, Self::UI_SET_ABSBIT, axis as libc::c_int))?;
+                    let abs = libc::uinput_abs_setup {
+                        code: axis,
+                        absinfo: libc::input_absinfo { value: 0, minimum: -32768, maximum: 32767, fuzz: 16, flat: 128, resolution: 0 },
+                    };
+                    check(libc::ioctl(fd, Self::UI_ABS_SETUP, &abs))?;
+                }
+                check(libc::ioctl(fd, Self::UI_DEV_SETUP, &setup))?;
+                check(libc::ioctl(fd, Self::UI_DEV_CREATE))?;
+            }
+            Ok(pad)
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    impl Drop for VirtualGamepad {
+        fn drop(&mut self) {
+            use std::os::fd::AsRawFd;
+
+            // SAFETY: the descriptor is still open, and this request takes no argument.
+            unsafe { libc::ioctl(self.0.as_raw_fd(), Self::UI_DEV_DESTROY) };
+        }
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    #[ignore = "needs write access to /dev/uinput and udev to announce the new device"]
+    fn connected_gamepad_is_named_on_the_next_snapshot() -> anyhow::Result<()> {
+        use std::time::{Duration, Instant};
+
+        const NAME: &str = "lyrebird virtual gamepad";
+
+        let input = InputManager::default();
+        let pad = VirtualGamepad::create(NAME)?;
+        // nothing pumps gamepads for a while, as when frames stop.
+        std::thread::sleep(Duration::from_millis(500));
+
+        let named = |input: &InputManager| {
+            input.gamepads_snapshot().gamepads.into_values().find(|state| state.info.name == NAME)
+        };
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let state = loop {
+            input.update_gamepads();
+            if let Some(state) = named(&input) {
+                break state;
+            }
+            anyhow::ensure!(Instant::now() < deadline, "gilrs never saw the virtual gamepad connect");
+            std::thread::sleep(Duration::from_millis(10));
+        };
+        assert!(state.info.is_connected);
+
+        drop(pad);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while named(&input).is_some_and(|state| state.info.is_connected) {
+            anyhow::ensure!(Instant::now() < deadline, "gilrs never saw the virtual gamepad disconnect");
+            input.update_gamepads();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,109 @@
+use std::{marker::PhantomData, ops::Range};
+
+use crate::GraphicsContext;
+
+/// A growable vertex buffer of per-instance data, for drawing many copies of the same mesh
+/// (tiles, particles, foliage) in a single draw call.
+///
+/// `T` is `#[repr(C)]` and `bytemuck::Pod`, with one field per attribute of the instance
+/// [Self::layout] in the pipeline. The buffer starts with room for a few instances and is
+/// reallocated to the next power of two whenever [Self::update] is given more than fit.
+pub struct InstanceBuffer<T: bytemuck::Pod> {
+    buffer: wgpu::Buffer,
+    capacity: usize,
+    len: u32,
+    label: String,
+    _instance: PhantomData<T>,
+}
+
+impl<T: bytemuck::Pod> InstanceBuffer<T> {
+    const INITIAL_CAPACITY: usize = 64;
+
+    pub fn new(ctx: &GraphicsContext, label: &str) -> Self {
+        Self {
+            buffer: Self::create_buffer(ctx, label, Self::INITIAL_CAPACITY),
+            capacity: Self::INITIAL_CAPACITY,
+            len: 0,
+            label: label.to_owned(),
+            _instance: PhantomData,
+        }
+    }
+
+    fn create_buffer(ctx: &GraphicsContext, label: &str, capacity: usize) -> wgpu::Buffer {
+        // room for the padding writes get when the instances aren't a whole number of words.
+        let size = ((capacity * size_of::<T>()) as wgpu::BufferAddress).next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT);
+        ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size: size.max(wgpu::COPY_BUFFER_ALIGNMENT),
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// The vertex buffer layout for a pipeline reading `T` once per instance through
+    /// `attributes`, such as `&wgpu::vertex_attr_array![2 => Float32x4]`. Keep the shader
+    /// locations clear of the ones the mesh's own vertex buffer uses.
+    pub fn layout(attributes: &[wgpu::VertexAttribute]) -> wgpu::VertexBufferLayout<'_> {
+        wgpu::VertexBufferLayout {
+            array_stride: size_of::<T>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes,
+        }
+    }
+
+    /// Replaces the instances with `instances`, growing the buffer first if they don't fit.
+    /// Like `queue.write_buffer`, the new data lands before the next submission.
+    pub fn update(&mut self, ctx: &GraphicsContext, instances: &[T]) {
+        if instances.len() > self.capacity {
+            self.capacity = instances.len().next_power_of_two();
+            self.buffer = Self::create_buffer(ctx, &self.label, self.capacity);
+        }
+
+        self.len = instances.len() as u32;
+        ctx.write_buffer_padded(&self.buffer, bytemuck::cast_slice(instances));
+    }
+
+    /// Number of instances from the last [Self::update].
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// How many instances fit before the buffer has to be reallocated.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+
+    /// Binds the instances to vertex buffer `slot` and draws `indices` of the bound index
+    /// buffer once for every instance, in one call. The pipeline, the mesh's vertex buffer
+    /// and its index buffer need to be set already.
+    pub fn draw_indexed(&self, pass: &mut wgpu::RenderPass, slot: u32, indices: Range<u32>) {
+        if self.is_empty() {
+            return;
+        }
+
+        pass.set_vertex_buffer(slot, self.slice());
+        pass.draw_indexed(indices, 0, 0..self.len);
+    }
+
+    /// Like [Self::draw_indexed], for meshes without an index buffer.
+    pub fn draw(&self, pass: &mut wgpu::RenderPass, slot: u32, vertices: Range<u32>) {
+        if self.is_empty() {
+            return;
+        }
+
+        pass.set_vertex_buffer(slot, self.slice());
+        pass.draw(vertices, 0..self.len);
+    }
+
+    fn slice(&self) -> wgpu::BufferSlice<'_> {
+        self.buffer.slice(..(self.len as usize * size_of::<T>()) as wgpu::BufferAddress)
+    }
+}
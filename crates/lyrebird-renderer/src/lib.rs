@@ -1,158 +1,424 @@
-use std::sync::Arc;
+use std::{sync::{Arc, atomic::{AtomicBool, Ordering}}, time::Duration};
 
-use slint::{ComponentHandle, wgpu_27::{WGPUConfiguration, WGPUSettings}};
-use wgpu::{Extent3d, Instance, TextureDescriptor};
+use slint::ComponentHandle;
+use web_time::Instant;
 
-use crate::{input::InputManager, scene::{AppBehaviour, Context}};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::input::GamepadConnection;
+use crate::{encoder::EncoderQueue, input::InputManager, profiler::GpuProfiler, scene::{AppBehaviour, AppShared, Context}};
 
+mod app;
+mod cache;
+mod camera;
+mod color;
+mod composite;
+mod compute;
+mod debug_draw;
+mod encoder;
+pub mod graphics2d;
 mod scene;
 mod input;
+mod instance;
+mod mipmap;
+mod target;
+mod headless;
+mod window;
+mod timing;
+mod pass;
+mod occlusion;
+mod profiler;
+mod query;
+mod replay;
+mod sampler;
+mod shader;
+mod staging;
+mod texture;
+mod uniform;
+mod stack;
+#[cfg(not(target_arch = "wasm32"))]
+mod asset;
+#[cfg(feature = "egui")]
+mod egui_layer;
+
+pub use app::AppBuilder;
+pub use headless::{run_headless, run_headless_with};
 
 pub mod prelude {
     pub use super::scene::*;
+    pub use super::camera::*;
+    pub use super::color::*;
+    pub use super::composite::*;
+    pub use super::compute::*;
+    pub use super::debug_draw::*;
+    pub use super::encoder::*;
     pub use super::input::*;
+    pub use super::instance::*;
+    pub use super::mipmap::*;
+    pub use super::target::*;
+    pub use super::window::*;
+    pub use super::timing::*;
+    pub use super::pass::*;
+    pub use super::occlusion::*;
+    pub use super::profiler::*;
+    pub use super::replay::*;
+    pub use super::sampler::*;
+    pub use super::shader::*;
+    pub use super::stack::*;
+    pub use super::texture::*;
+    pub use super::uniform::*;
+    #[cfg(not(target_arch = "wasm32"))]
+    pub use super::asset::*;
 
+    pub use glam;
     pub use wgpu;
     pub use winit;
     #[cfg(not(target_arch = "wasm32"))]
     pub use gilrs;
+    #[cfg(feature = "egui")]
+    pub use egui;
 }
 
 /// A version of [State] that can be passed around thread-safe.  
 pub struct GraphicsContext {
     pub device: Arc<wgpu::Device>,
     pub queue: Arc<wgpu::Queue>,
+    instance: wgpu::Instance,
+    adapter: Option<wgpu::Adapter>,
+    lost: Arc<AtomicBool>,
+    out_of_memory: Arc<AtomicBool>,
+    errors: Arc<parking_lot::Mutex<Vec<wgpu::Error>>>,
+    belt: parking_lot::Mutex<wgpu::util::StagingBelt>,
+    pipelines: parking_lot::Mutex<cache::PipelineCache>,
+    bind_groups: parking_lot::Mutex<cache::BindGroupCache>,
+    samplers: parking_lot::Mutex<sampler::SamplerCache>,
+    mipmaps: std::sync::OnceLock<mipmap::MipmapGenerator>,
+}
+
+impl GraphicsContext {
+    pub(crate) fn new(instance: wgpu::Instance, adapter: Option<wgpu::Adapter>, device: wgpu::Device, queue: wgpu::Queue) -> Self {
+        let lost = Arc::new(AtomicBool::new(false));
+        let flag = lost.clone();
+        device.set_device_lost_callback(move |reason, message| {
+            match reason {
+                wgpu::DeviceLostReason::Destroyed => log::info!("Device destroyed: {message}"),
+                wgpu::DeviceLostReason::Unknown => {
+                    log::error!("Device lost: {message}");
+                    flag.store(true, Ordering::Release);
+                }
+            }
+        });
+
+        // running out of memory can't be recovered from, so it is flagged for the render loop to
+        // shut down on. Anything else only invalidates what it happened to, so it is logged and
+        // kept for the scene to hear about, rather than panicking from inside wgpu like the
+        // default handler does.
+        let out_of_memory = Arc::new(AtomicBool::new(false));
+        let flag = out_of_memory.clone();
+        let errors: Arc<parking_lot::Mutex<Vec<wgpu::Error>>> = Arc::default();
+        let reported = errors.clone();
+        device.on_uncaptured_error(Arc::new(move |error| match error {
+            wgpu::Error::OutOfMemory { .. } => {
+                log::error!("GPU out of memory: {error}");
+                flag.store(true, Ordering::Release);
+            }
+            wgpu::Error::Validation { .. } | wgpu::Error::Internal { .. } => {
+                log::error!("wgpu error: {error}");
+                reported.lock().push(error);
+            }
+        }));
+
+        Self {
+            device: Arc::new(device),
+            queue: Arc::new(queue),
+            instance,
+            adapter,
+            lost,
+            out_of_memory,
+            errors,
+            belt: parking_lot::Mutex::new(wgpu::util::StagingBelt::new(staging::STAGING_CHUNK_SIZE)),
+            pipelines: Default::default(),
+            bind_groups: Default::default(),
+            samplers: Default::default(),
+            mipmaps: Default::default(),
+        }
+    }
+
+    /// The instance the device was created from, for making further surfaces or anything else
+    /// wgpu needs an instance for.
+    pub fn instance(&self) -> &wgpu::Instance {
+        &self.instance
+    }
+
+    /// The adapter the device was requested from, for its info, features, limits and format
+    /// support. lyrebird picks the adapter itself, headless and in a window alike, so this is
+    /// only `None` on the web, where Slint requests the adapter asynchronously and doesn't hand
+    /// it back.
+    pub fn adapter(&self) -> Option<&wgpu::Adapter> {
+        self.adapter.as_ref()
+    }
+
+    /// Loses the device on purpose, the way a driver reset would, for testing how a scene
+    /// copes with [AppBehaviour::device_lost]. The render loop notices at the start of the
+    /// next frame, or headless straight after the frame renders.
+    #[cfg(any(test, feature = "debug"))]
+    pub fn simulate_device_loss(&self) {
+        log::warn!("Simulating device loss");
+        self.lost.store(true, Ordering::Release);
+        self.device.destroy();
+    }
+
+    /// Returns true once the device has been lost (driver reset, GPU removed, ...). Every
+    /// resource created from it is invalid; wait for [AppBehaviour::device_lost] to recreate them.
+    pub fn is_lost(&self) -> bool {
+        self.lost.load(Ordering::Acquire)
+    }
+
+    /// Returns true once the device has reported running out of memory. This is fatal: the
+    /// app is told through [AppBehaviour::out_of_memory] and then shut down.
+    pub fn is_out_of_memory(&self) -> bool {
+        self.out_of_memory.load(Ordering::Acquire)
+    }
+
+    /// The validation and internal errors wgpu reported since the last call, which the render
+    /// loop hands to [AppBehaviour::gpu_error] once a frame.
+    pub(crate) fn take_errors(&self) -> Vec<wgpu::Error> {
+        std::mem::take(&mut *self.errors.lock())
+    }
+
+    /// The limits the device was created with, for scenes that scale what they ask for (texture
+    /// sizes, buffer bindings) to the hardware. Unless [AppBuilder::limits] picked them, these
+    /// are wgpu's defaults, or a downlevel set on adapters that can't meet those.
+    pub fn limits(&self) -> wgpu::Limits {
+        self.device.limits()
+    }
 }
 
+/// The limits to create `adapter`'s device with when the app didn't ask for any: wgpu's
+/// defaults, stepping down to the downlevel and then the WebGL2 downlevel defaults for older
+/// or weaker hardware that can't meet them. If even those are too much, the least of them is
+/// returned for `request_device` to explain what's missing.
+pub(crate) fn default_limits(adapter: &wgpu::Adapter) -> wgpu::Limits {
+    let supported = adapter.limits();
+    let tiers = [
+        ("default", wgpu::Limits::default()),
+        ("downlevel", wgpu::Limits::downlevel_defaults()),
+        ("WebGL2 downlevel", wgpu::Limits::downlevel_webgl2_defaults()),
+    ];
+    for (tier, (name, limits)) in tiers.iter().enumerate() {
+        if limits.check_limits(&supported) {
+            if tier > 0 {
+                log::warn!("{} can't meet wgpu's default limits, using the {name} limits instead", adapter.get_info().name);
+            }
+            return limits.clone();
+        }
+    }
+    log::warn!("{} can't meet even the WebGL2 downlevel limits", adapter.get_info().name);
+    wgpu::Limits::downlevel_webgl2_defaults()
+}
+
+/// Fails with the limits `adapter` falls short of, when it can't meet `limits`.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn ensure_limits(limits: &wgpu::Limits, adapter: &wgpu::Adapter) -> anyhow::Result<()> {
+    let mut missing = Vec::new();
+    limits.check_limits_with_fail_fn(&adapter.limits(), false, |name, requested, allowed| {
+        missing.push(format!("{name} (asked for {requested}, it has {allowed})"));
+    });
+    anyhow::ensure!(
+        missing.is_empty(),
+        "{} can't meet the limits the app asked for: {}",
+        adapter.get_info().name,
+        missing.join(", ")
+    );
+    Ok(())
+}
+
+/// Requests the device lyrebird renders with from `adapter`, failing with what's missing when
+/// the adapter doesn't have `features`.
+pub(crate) fn request_device(
+    adapter: &wgpu::Adapter,
+    label: &str,
+    features: wgpu::Features,
+    limits: wgpu::Limits,
+) -> anyhow::Result<(wgpu::Device, wgpu::Queue)> {
+    let missing = features - adapter.features();
+    anyhow::ensure!(
+        missing.is_empty(),
+        "{} doesn't support the features the app asked for: {missing:?}",
+        adapter.get_info().name
+    );
+    let device = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+        label: Some(label),
+        required_features: features,
+        required_limits: limits,
+        ..Default::default()
+    }))?;
+    Ok(device)
+}
+
+/// Turns a failed adapter request into an error worth reporting. wgpu's message already says
+/// what became of each backend; this adds whether software adapters were tried and what to do
+/// about it, which is what users on VMs and GPU-less CI machines need to hear.
+pub(crate) fn no_adapter_error(error: wgpu::RequestAdapterError, tried_fallback: bool) -> anyhow::Error {
+    let fallback = if tried_fallback {
+        "a fallback (software) adapter was requested too, with force_fallback_adapter, and none was found either"
+    } else {
+        "fallback (software) adapters were not tried"
+    };
+    anyhow::anyhow!(
+        "no graphics adapter available: {error}. Backends compiled in: {:?}; {fallback}. Check the GPU \
+         drivers, or on a machine without a GPU install a software driver such as lavapipe or \
+         llvmpipe (Linux) or WARP (Windows). WGPU_BACKEND and WGPU_ADAPTER_NAME choose the \
+         backend and adapter",
+        wgpu::Instance::enabled_backend_features()
+    )
+}
+
+/// The longest `dt` a frame is given unless [AppBuilder::max_dt] says otherwise.
+pub(crate) const DEFAULT_MAX_DT: Duration = Duration::from_millis(100);
+
+/// The most [AppBehaviour::fixed_update] steps a single frame runs.
+const MAX_FIXED_STEPS: u32 = 8;
+
 pub struct State {
-    #[allow(dead_code)]
-    instance: Instance,
     ctx: Arc<GraphicsContext>,
     input_manager: InputManager,
+    start: Instant,
+    dt: Duration,
+    max_dt: Option<Duration>,
+    dt_clamped: bool,
+    clear_color: wgpu::Color,
+    viewport_size: (u32, u32),
+    render_scale: f32,
+    surface_ready: bool,
+    viewport_format: wgpu::TextureFormat,
+    /// Time not yet simulated by [AppBehaviour::fixed_update], always less than a step.
+    fixed_accumulator: Duration,
+    interpolation_alpha: f32,
+    shared: Arc<AppShared>,
+    profiler: GpuProfiler,
+    encoders: EncoderQueue,
 }
 
 impl State {
     pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+    pub(crate) fn context(&self) -> Context {
+        Context {
+            graphics: self.ctx.clone(),
+            input: self.input_manager.clone(),
+            start: self.start,
+            dt: self.dt,
+            dt_clamped: self.dt_clamped,
+            clear_color: self.clear_color,
+            viewport_size: self.viewport_size,
+            render_scale: self.render_scale,
+            surface_ready: self.surface_ready,
+            viewport_format: self.viewport_format,
+            interpolation_alpha: self.interpolation_alpha,
+            shared: self.shared.clone(),
+            profiler: self.profiler.clone(),
+            encoders: self.encoders.clone(),
+        }
+    }
+
+    /// Sets this frame's `dt`, cut down to [Self::max_dt] if it is longer.
+    pub(crate) fn set_dt(&mut self, dt: Duration) {
+        let max = self.max_dt.unwrap_or(Duration::MAX);
+        self.dt_clamped = dt > max;
+        if self.dt_clamped {
+            log::debug!("Frame took {dt:?}, clamping dt to {max:?}");
+        }
+        self.dt = dt.min(max);
+    }
+
+    /// Runs as many [AppBehaviour::fixed_update] steps as this frame's `dt` makes up, and works
+    /// out how far the frame is into the next one. A frame never runs more than
+    /// [MAX_FIXED_STEPS]; time beyond that, after a hitch, is dropped rather than caught up
+    /// on, which would only make the next frame slower still.
+    pub(crate) fn fixed_updates<S: AppBehaviour>(&mut self, app: &mut S) {
+        let Some(step) = app.fixed_timestep().filter(|step| !step.is_zero()) else {
+            self.fixed_accumulator = Duration::ZERO;
+            self.interpolation_alpha = 1.0;
+            return;
+        };
+
+        self.fixed_accumulator += self.dt;
+        let ctx = self.context();
+        let mut steps = 0;
+        while self.fixed_accumulator >= step {
+            if steps == MAX_FIXED_STEPS {
+                log::debug!("Dropping {:?} of fixed updates this frame", self.fixed_accumulator);
+                self.fixed_accumulator = Duration::from_secs_f64(self.fixed_accumulator.as_secs_f64() % step.as_secs_f64());
+                break;
+            }
+            app.fixed_update(&ctx, step.as_secs_f64());
+            self.fixed_accumulator -= step;
+            steps += 1;
+        }
+        self.interpolation_alpha = (self.fixed_accumulator.as_secs_f64() / step.as_secs_f64()) as f32;
+    }
+
+    /// Hands the GPU errors reported since the last frame to the app.
+    pub(crate) fn report_gpu_errors<S: AppBehaviour>(&self, app: &mut S) {
+        let errors = self.ctx.take_errors();
+        if errors.is_empty() {
+            return;
+        }
+        let ctx = self.context();
+        for error in errors {
+            app.gpu_error(&ctx, &error);
+        }
+    }
+
+    pub(crate) fn is_exit_requested(&self) -> bool {
+        self.shared.exit_requested.load(Ordering::Acquire)
+    }
+
+    /// Replaces a lost device with a new one from the same adapter, with the same features and
+    /// limits. Everything else, the input included, carries on as it was; the caller tells the
+    /// scene through [AppBehaviour::device_lost].
+    pub(crate) fn recreate_device(&mut self, label: &str) -> anyhow::Result<()> {
+        let adapter = self
+            .ctx
+            .adapter()
+            .ok_or_else(|| anyhow::anyhow!("the adapter isn't known, so there's nothing to request a new device from"))?
+            .clone();
+        let (device, queue) = request_device(&adapter, label, self.ctx.device.features(), self.ctx.limits())?;
+        self.ctx = Arc::new(GraphicsContext::new(self.ctx.instance.clone(), Some(adapter), device, queue));
+        self.profiler = GpuProfiler::new(&self.ctx);
+        self.encoders = Default::default();
+        Ok(())
+    }
+
+    /// Pumps gamepad events and tells the scene about any controllers that came or went.
+    /// Returns whether any gamepad events came in.
+    #[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+    pub(crate) fn update_gamepads<S: AppBehaviour>(&self, app: &mut S) -> bool {
+        let active = self.input_manager.pump_gamepads();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        for connection in self.input_manager.take_gamepad_connections() {
+            match connection {
+                GamepadConnection::Connected(id, info) => app.gamepad_connected(&self.context(), id, info),
+                GamepadConnection::Disconnected(id) => app.gamepad_disconnected(&self.context(), id),
+            }
+        }
+        active
+    }
 }
 
+/// Runs the app with the default [AppBuilder] settings.
 pub fn run<S>() -> anyhow::Result<()> 
 where 
-    S: ComponentHandle + AppBehaviour + 'static,
+    S: ComponentHandle + AppBehaviour<Args = ()> + 'static,
 {
-    #[cfg(not(target_arch = "wasm32"))]
-    {
-        env_logger::init();
-    }
-    #[cfg(target_arch = "wasm32")]
-    {
-        console_log::init_with_level(log::Level::Info).unwrap_throw();
-    }
-
-    slint::BackendSelector::new()
-        .require_wgpu_27(WGPUConfiguration::Automatic(WGPUSettings::default()))
-        .select()
-        .expect("Unable to create Slint backend with WGPU based renderer");
-
-    let slint_app = S::new();
-
-    let mut last_frame = std::time::Instant::now();
-    let mut offscreen_texture: Option<wgpu::Texture> = None;
-    let mut old_size = slint_app.window().size();
-    let mut renderer = None;
-    let mut app = slint_app.clone_strong();
-    slint_app.window().set_rendering_notifier(move |state, api| {
-        match state {
-            slint::RenderingState::RenderingSetup => {
-                if let slint::GraphicsAPI::WGPU27 { instance, device, queue, .. } = api {
-                    let ctx = GraphicsContext {
-                        device:  Arc::new(device.clone()),
-                        queue: Arc::new(queue.clone()),
-                    };
-
-                    let state = State {
-                        instance: instance.clone(),
-                        ctx: Arc::new(ctx),
-                        input_manager: InputManager::default(),
-                    };
-
-                    renderer = Some(state);
-                }
-            },
-            slint::RenderingState::BeforeRendering => {
-                if let Some(state) = &renderer {
-                    // use i_slint_backend_winit::WinitWindowAccessor;
-
-                    let now = std::time::Instant::now();
-                    let dt = now.duration_since(last_frame).as_secs_f64();
-                    last_frame = now;
-
-                    // if InputManager::is_input_event(&event) {
-                    //     state.input_manager.poll(event.clone());
-                    // }
-
-                    state.input_manager.update_gamepads();
-
-                    app.update(
-                        Context { 
-                            graphics: state.ctx.clone(), 
-                            input: state.input_manager.clone(),
-                        },
-                        dt
-                    );
-
-                    let size = app.window().size();
-                    let width = size.width;
-                    let height = size.height;
-
-                    if offscreen_texture.is_none() || old_size != size {
-                        old_size = size;
-                        offscreen_texture = Some(state.ctx.device.create_texture(&TextureDescriptor {
-                            label: Some("viewport texture"),
-                            size: Extent3d {
-                                width: width.max(1),
-                                height: height.max(1),
-                                depth_or_array_layers: 1,
-                            },
-                            mip_level_count: 1,
-                            sample_count: 1,
-                            dimension: wgpu::TextureDimension::D2,
-                            format: State::FORMAT,
-                            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
-                            view_formats: &[],
-                        }));
-                    }
-                    let texture = offscreen_texture.as_ref().unwrap();
-                    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-
-                    app.render(
-                        Context {
-                            graphics: state.ctx.clone(),
-                            input: state.input_manager.clone(),
-                        },
-                        &view
-                    );
-
-                    // app.set_texture(slint::Image::try_from(texture.clone()).unwrap());
-
-                    app.window().request_redraw();
-                }
-
-                app.window().request_redraw();
-            }
-            slint::RenderingState::AfterRendering => {},
-            slint::RenderingState::RenderingTeardown => {
-                if let Some(state) = &renderer {
-                    app.exiting(Context {
-                        graphics: state.ctx.clone(),
-                        input: state.input_manager.clone(),
-                    });
-                    log::info!("Exiting app");
-                }
-                drop(renderer.take());
-            },
-            _ => todo!(),
-        }
-    }).unwrap();
+    run_with::<S>(())
+}
 
-    Ok(slint_app.run()?)
-}
\ No newline at end of file
+/// Like [run], but constructs the app from `args`.
+pub fn run_with<S>(args: S::Args) -> anyhow::Result<()>
+where
+    S: ComponentHandle + AppBehaviour + 'static,
+{
+    AppBuilder::new().run_with::<S>(args)
+}
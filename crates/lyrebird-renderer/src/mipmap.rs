@@ -0,0 +1,179 @@
+use crate::{GraphicsContext, pass::RenderPassBuilder};
+
+/// The shader and layouts [GraphicsContext::generate_mipmaps] draws with, created the first
+/// time a texture needs mipmaps. Pipelines differ by format and come from the pipeline cache.
+pub(crate) struct MipmapGenerator {
+    shader: wgpu::ShaderModule,
+    bind_group_layout: wgpu::BindGroupLayout,
+    layout: wgpu::PipelineLayout,
+}
+
+impl MipmapGenerator {
+    fn new(device: &wgpu::Device) -> Self {
+        let shader = device.create_shader_module(wgpu::include_wgsl!("mipmap.wgsl"));
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("mipmap bind group layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("mipmap pipeline layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        Self {
+            shader,
+            bind_group_layout,
+            layout,
+        }
+    }
+}
+
+/// How many mip levels a texture gets, see [crate::prelude::Texture::with_mipmaps].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MipLevels {
+    /// Halving all the way down to 1x1.
+    #[default]
+    Full,
+    /// At most this many levels, counting the full-size one. Textures far from the camera
+    /// rarely need the smallest levels, and leaving them out saves a few draws.
+    Count(u32),
+}
+
+impl MipLevels {
+    /// The number of levels this gives a `width`x`height` texture, which is never more than the
+    /// full chain nor less than 1.
+    pub fn count(self, width: u32, height: u32) -> u32 {
+        let full = u32::BITS - width.max(height).max(1).leading_zeros();
+        match self {
+            Self::Full => full,
+            Self::Count(count) => count.clamp(1, full),
+        }
+    }
+}
+
+impl GraphicsContext {
+    /// Fills every mip level of `texture` below the first by repeatedly halving the one above
+    /// it with a linear filter. wgpu has nothing built in for this, so it is done with a render
+    /// pass per level.
+    ///
+    /// `texture` has to be a 2D texture with `RENDER_ATTACHMENT` and `TEXTURE_BINDING` usage,
+    /// in a format that can be both rendered to and filtered, which rules out integer, depth and
+    /// compressed formats. Array layers past the first are left alone.
+    pub fn generate_mipmaps(&self, texture: &wgpu::Texture) -> anyhow::Result<()> {
+        let format = texture.format();
+        self.ensure_mipmappable(format)?;
+        anyhow::ensure!(
+            texture
+                .usage()
+                .contains(wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING),
+            "generating mipmaps needs RENDER_ATTACHMENT and TEXTURE_BINDING usage, the texture has {:?}",
+            texture.usage()
+        );
+        if texture.mip_level_count() < 2 {
+            return Ok(());
+        }
+
+        let generator = self.mipmaps.get_or_init(|| MipmapGenerator::new(&self.device));
+        let pipeline = self.cached_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("mipmap pipeline"),
+            layout: Some(&generator.layout),
+            vertex: wgpu::VertexState {
+                module: &generator.shader,
+                entry_point: Some("vs_main"),
+                compilation_options: Default::default(),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &generator.shader,
+                entry_point: Some("fs_main"),
+                compilation_options: Default::default(),
+                targets: &[Some(format.into())],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+        let sampler = self.linear_clamp();
+
+        let level_view = |level| {
+            texture.create_view(&wgpu::TextureViewDescriptor {
+                label: Some("mip level"),
+                dimension: Some(wgpu::TextureViewDimension::D2),
+                base_mip_level: level,
+                mip_level_count: Some(1),
+                base_array_layer: 0,
+                array_layer_count: Some(1),
+                ..Default::default()
+            })
+        };
+
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Mipmap Encoder"),
+        });
+        let mut source = level_view(0);
+        for level in 1..texture.mip_level_count() {
+            let target = level_view(level);
+            let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("mipmap bind group"),
+                layout: &generator.bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&source),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&sampler),
+                    },
+                ],
+            });
+
+            {
+                let mut pass = RenderPassBuilder::new(&target)
+                    .label("Mipmap Pass")
+                    .clear(wgpu::Color::TRANSPARENT)
+                    .begin(&mut encoder);
+                pass.set_pipeline(&pipeline);
+                pass.set_bind_group(0, &bind_group, &[]);
+                pass.draw(0..3, 0..1);
+            }
+            source = target;
+        }
+        self.submit(encoder);
+        Ok(())
+    }
+
+    /// Fails unless mipmaps can be generated for textures in `format` on this device, which
+    /// draws each level from the one above, so the format has to be renderable and filterable.
+    pub(crate) fn ensure_mipmappable(&self, format: wgpu::TextureFormat) -> anyhow::Result<()> {
+        let device_features = self.device.features();
+        let features = format.guaranteed_format_features(device_features);
+        anyhow::ensure!(
+            features.allowed_usages.contains(wgpu::TextureUsages::RENDER_ATTACHMENT)
+                && features.flags.contains(wgpu::TextureFormatFeatureFlags::FILTERABLE)
+                && format.sample_type(None, Some(device_features)) == Some(wgpu::TextureSampleType::Float { filterable: true }),
+            "can't generate mipmaps for {format:?}, as it isn't renderable and filterable"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,82 @@
+use crate::{GraphicsContext, query::QueryReadback};
+
+/// A set of hardware occlusion queries, for skipping draws of objects that ended up entirely
+/// hidden last frame.
+///
+/// Attach it to a pass with [crate::prelude::RenderPassBuilder::occlusion_queries], and wrap
+/// the draws for query `i` (usually a cheap bounding box) in
+/// `pass.begin_occlusion_query(i)` / `pass.end_occlusion_query()`. Queries only count samples
+/// that pass the depth test, so the pass needs a depth attachment for them to mean anything.
+/// After the pass, call [Self::resolve] with the same encoder.
+///
+/// Results are read back without stalling the GPU, which means they are always at least a
+/// frame old: what [Self::is_visible] reports during frame N comes from frame N-1 at best, and
+/// from an earlier frame if the GPU is falling behind. An object that comes into view is drawn
+/// one frame late unless it is drawn conservatively, such as always drawing anything whose
+/// query hasn't come back yet, which is what [Self::is_visible] does.
+///
+/// Occlusion queries are part of core WebGPU, so unlike timestamp or pipeline statistics
+/// queries they don't need a device feature and work on every adapter.
+pub struct OcclusionQuerySet {
+    set: wgpu::QuerySet,
+    count: u32,
+    readback: QueryReadback,
+    samples: Option<Vec<u64>>,
+}
+
+impl OcclusionQuerySet {
+    pub fn new(ctx: &GraphicsContext, count: u32) -> Self {
+        let set = ctx.device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("occlusion queries"),
+            ty: wgpu::QueryType::Occlusion,
+            count,
+        });
+
+        Self {
+            set,
+            count,
+            readback: QueryReadback::new(ctx, "occlusion queries", count),
+            samples: None,
+        }
+    }
+
+    /// The underlying query set, for passes that aren't built with
+    /// [crate::prelude::RenderPassBuilder].
+    pub fn query_set(&self) -> &wgpu::QuerySet {
+        &self.set
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// Picks up any results that have finished reading back, then records copying this frame's
+    /// results into `encoder` if the readback buffer is free. Call it once per frame after the
+    /// pass that ran the queries, and submit `encoder` before the next call.
+    pub fn resolve(&mut self, ctx: &GraphicsContext, encoder: &mut wgpu::CommandEncoder) {
+        if let Some(mut samples) = self.readback.fetch(ctx) {
+            samples.truncate(self.count as usize);
+            self.samples = Some(samples);
+        }
+        self.readback.resolve(encoder, &self.set, 0..self.count);
+    }
+
+    /// How many samples query `index` counted in the latest results to come back, or `None` if
+    /// none have yet. Some backends only report zero or non-zero rather than an exact count.
+    pub fn samples(&self, index: u32) -> Option<u64> {
+        self.samples.as_ref()?.get(index as usize).copied()
+    }
+
+    /// Whether anything covered by query `index` was visible in the latest results. Before
+    /// any results come back this is `true`, so nothing is culled on a guess.
+    pub fn is_visible(&self, index: u32) -> bool {
+        self.samples(index).is_none_or(|samples| samples > 0)
+    }
+}
+
+impl GraphicsContext {
+    /// Creates an [OcclusionQuerySet] with room for `count` queries per pass.
+    pub fn create_occlusion_query_set(&self, count: u32) -> OcclusionQuerySet {
+        OcclusionQuerySet::new(self, count)
+    }
+}
@@ -0,0 +1,126 @@
+use crate::{GraphicsContext, occlusion::OcclusionQuerySet, profiler::GpuProfiler, target::RenderTarget};
+
+/// What a pass started with [crate::prelude::Context::begin_pass] does with what's already in
+/// its view.
+///
+/// The view handed to [crate::prelude::AppBehaviour::render] is an offscreen target that
+/// lyrebird keeps from one frame to the next, not a swapchain image, so [Self::Load] works
+/// across frames too: trails, paint programs and progressive rendering can build on the
+/// previous frame. It starts out transparent black, and is again whenever it is recreated,
+/// which happens on resizes, on [crate::prelude::AppBehaviour::render_scale] changes and after
+/// a device loss. A swapchain image's contents are undefined once it has been presented, so on
+/// a surface of your own, `Load` only makes sense for passes after the first in a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum LoadPolicy {
+    /// Clear to [crate::prelude::Context::clear_color].
+    #[default]
+    Clear,
+    /// Clear to this colour instead.
+    ClearTo(wgpu::Color),
+    /// Keep the existing contents and draw over them.
+    Load,
+}
+
+/// Fills in the boilerplate of a [wgpu::RenderPassDescriptor] for the common case of one colour
+/// attachment and an optional depth attachment.
+pub struct RenderPassBuilder<'a> {
+    label: Option<&'a str>,
+    view: &'a wgpu::TextureView,
+    load: wgpu::LoadOp<wgpu::Color>,
+    depth: Option<&'a wgpu::TextureView>,
+    occlusion: Option<&'a wgpu::QuerySet>,
+    timestamps: Option<wgpu::RenderPassTimestampWrites<'a>>,
+}
+
+impl<'a> RenderPassBuilder<'a> {
+    /// Starts a pass that renders into `view`, clearing it to black.
+    pub fn new(view: &'a wgpu::TextureView) -> Self {
+        Self {
+            label: Some("Render Pass"),
+            view,
+            load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+            depth: None,
+            occlusion: None,
+            timestamps: None,
+        }
+    }
+
+    /// Starts a pass that renders into a [RenderTarget], wiring up its depth attachment if it
+    /// has one.
+    pub fn for_target(target: &'a RenderTarget) -> Self {
+        let mut builder = Self::new(target.view());
+        builder.depth = target.depth_view();
+        builder
+    }
+
+    pub fn label(mut self, label: &'a str) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    /// Clears the colour attachment to `color` at the start of the pass.
+    pub fn clear(mut self, color: wgpu::Color) -> Self {
+        self.load = wgpu::LoadOp::Clear(color);
+        self
+    }
+
+    /// Keeps what is already in the colour attachment, for drawing on top of an earlier pass.
+    pub fn load(mut self) -> Self {
+        self.load = wgpu::LoadOp::Load;
+        self
+    }
+
+    /// Attaches a depth buffer, which is cleared to 1.0 at the start of the pass.
+    pub fn depth(mut self, view: &'a wgpu::TextureView) -> Self {
+        self.depth = Some(view);
+        self
+    }
+
+    /// Lets the pass run the queries in `queries`, see [OcclusionQuerySet].
+    pub fn occlusion_queries(mut self, queries: &'a OcclusionQuerySet) -> Self {
+        self.occlusion = Some(queries.query_set());
+        self
+    }
+
+    /// Times the pass on the GPU under `label`, see [GpuProfiler]. Does nothing if the device
+    /// can't.
+    pub fn timed(mut self, profiler: &'a GpuProfiler, label: &str) -> Self {
+        self.timestamps = profiler.timestamp_writes(label);
+        self
+    }
+
+    pub fn begin(self, encoder: &'a mut wgpu::CommandEncoder) -> wgpu::RenderPass<'a> {
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: self.label,
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: self.view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: self.load,
+                    store: wgpu::StoreOp::Store,
+                },
+                depth_slice: None,
+            })],
+            depth_stencil_attachment: self.depth.map(|view| wgpu::RenderPassDepthStencilAttachment {
+                view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            occlusion_query_set: self.occlusion,
+            timestamp_writes: self.timestamps,
+        })
+    }
+}
+
+impl GraphicsContext {
+    /// Records a pass into `encoder` that does nothing but clear `view` to `color`.
+    pub fn clear(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView, color: wgpu::Color) {
+        RenderPassBuilder::new(view)
+            .label("Clear Pass")
+            .clear(color)
+            .begin(encoder);
+    }
+}
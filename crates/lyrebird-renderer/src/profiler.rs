@@ -0,0 +1,141 @@
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use crate::{GraphicsContext, query::QueryReadback};
+
+/// How many passes a frame can time. Passes beyond this simply go untimed.
+const MAX_PASSES: u32 = 64;
+
+/// How long one render pass took on the GPU.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PassTiming {
+    pub label: String,
+    pub millis: f64,
+}
+
+struct ProfilerInner {
+    /// Labels of the passes timed so far this frame, in query order.
+    labels: Vec<String>,
+    /// Labels of the frame whose timestamps are on their way back.
+    in_flight: Vec<String>,
+    readback: QueryReadback,
+    timings: Vec<PassTiming>,
+}
+
+/// Times render passes on the GPU with timestamp queries, available to scenes through
+/// [crate::prelude::Context::gpu_profiler].
+///
+/// Build a pass with [crate::prelude::RenderPassBuilder::timed] and its duration shows up in
+/// [Self::timings] a frame or two later, once the timestamps have been read back without
+/// stalling.
+///
+/// This needs [wgpu::Features::TIMESTAMP_QUERY], which windowed apps have to ask for with
+/// [crate::AppBuilder::features] and headless runs request whenever the adapter has it.
+/// Without it the profiler does nothing and reports no timings, so scenes can time their
+/// passes unconditionally.
+#[derive(Clone)]
+pub struct GpuProfiler {
+    set: Option<wgpu::QuerySet>,
+    period: f32,
+    inner: Option<Arc<Mutex<ProfilerInner>>>,
+}
+
+impl GpuProfiler {
+    pub(crate) fn new(ctx: &GraphicsContext) -> Self {
+        if !ctx.device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            return Self {
+                set: None,
+                period: 0.0,
+                inner: None,
+            };
+        }
+
+        let set = ctx.device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("pass timestamps"),
+            ty: wgpu::QueryType::Timestamp,
+            count: MAX_PASSES * 2,
+        });
+        let inner = ProfilerInner {
+            labels: Vec::new(),
+            in_flight: Vec::new(),
+            readback: QueryReadback::new(ctx, "pass timestamps", MAX_PASSES * 2),
+            timings: Vec::new(),
+        };
+
+        Self {
+            set: Some(set),
+            period: ctx.queue.get_timestamp_period(),
+            inner: Some(Arc::new(Mutex::new(inner))),
+        }
+    }
+
+    /// Whether the device supports timestamp queries, and so whether there will be any timings.
+    pub fn is_supported(&self) -> bool {
+        self.set.is_some()
+    }
+
+    /// Timestamp writes for the start and end of a pass called `label`, or `None` if the
+    /// device can't time passes or this frame already timed as many as it can.
+    pub fn timestamp_writes(&self, label: &str) -> Option<wgpu::RenderPassTimestampWrites<'_>> {
+        let (set, inner) = self.set.as_ref().zip(self.inner.as_ref())?;
+
+        let mut inner = inner.lock();
+        let index = inner.labels.len() as u32;
+        if index == MAX_PASSES {
+            return None;
+        }
+        inner.labels.push(label.to_owned());
+
+        Some(wgpu::RenderPassTimestampWrites {
+            query_set: set,
+            beginning_of_pass_write_index: Some(index * 2),
+            end_of_pass_write_index: Some(index * 2 + 1),
+        })
+    }
+
+    /// Every pass timed in the latest frame to come back, in the order they were begun.
+    pub fn timings(&self) -> Vec<PassTiming> {
+        self.inner
+            .as_ref()
+            .map(|inner| inner.lock().timings.clone())
+            .unwrap_or_default()
+    }
+
+    /// Picks up finished timings and sends this frame's timestamps on their way back. Called by
+    /// the app loop after the scene's passes have been submitted.
+    pub(crate) fn end_frame(&self, ctx: &GraphicsContext) {
+        let (Some(set), Some(inner)) = (&self.set, &self.inner) else {
+            return;
+        };
+        let mut inner = inner.lock();
+
+        if let Some(timestamps) = inner.readback.fetch(ctx) {
+            let period = self.period as f64;
+            inner.timings = std::mem::take(&mut inner.in_flight)
+                .into_iter()
+                .zip(timestamps.chunks_exact(2))
+                .map(|(label, pair)| PassTiming {
+                    label,
+                    millis: pair[1].saturating_sub(pair[0]) as f64 * period / 1_000_000.0,
+                })
+                .collect();
+        }
+
+        let passes = inner.labels.len() as u32;
+        if passes == 0 {
+            return;
+        }
+
+        let mut encoder = ctx.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("pass timestamps resolve encoder"),
+        });
+        // if the last frame's timestamps are still on their way, this frame's are dropped.
+        if inner.readback.resolve(&mut encoder, set, 0..passes * 2) {
+            inner.in_flight = std::mem::take(&mut inner.labels);
+            ctx.queue.submit(std::iter::once(encoder.finish()));
+        } else {
+            inner.labels.clear();
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use std::{ops::Range, sync::Arc};
+
+use parking_lot::Mutex;
+
+use crate::GraphicsContext;
+
+/// Where the readback buffer is in its trip to the CPU and back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Readback {
+    /// Free to have the next results copied into it.
+    Idle,
+    /// A copy has been recorded, and is assumed to be submitted by the next [QueryReadback::fetch].
+    Copied,
+    /// Waiting on `map_async`.
+    Mapping,
+}
+
+/// Gets query results back to the CPU without stalling the GPU, one batch at a time, so they
+/// always arrive at least a frame after they were written.
+pub(crate) struct QueryReadback {
+    resolve: wgpu::Buffer,
+    readback: wgpu::Buffer,
+    state: Readback,
+    mapped: Arc<Mutex<Option<Result<(), wgpu::BufferAsyncError>>>>,
+}
+
+impl QueryReadback {
+    /// Makes room for `count` queries.
+    pub(crate) fn new(ctx: &GraphicsContext, label: &str, count: u32) -> Self {
+        // one u64 per query. resolving always starts at offset 0, which keeps it aligned to
+        // `QUERY_RESOLVE_BUFFER_ALIGNMENT`.
+        let size = count.max(1) as wgpu::BufferAddress * wgpu::QUERY_SIZE as wgpu::BufferAddress;
+        let resolve = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(&format!("{label} resolve")),
+            size,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(&format!("{label} readback")),
+            size,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            resolve,
+            readback,
+            state: Readback::Idle,
+            mapped: Arc::default(),
+        }
+    }
+
+    /// The results of the last [Self::resolve], once they've made it back.
+    pub(crate) fn fetch(&mut self, ctx: &GraphicsContext) -> Option<Vec<u64>> {
+        if self.state == Readback::Copied {
+            // the copy recorded last time has been submitted by now.
+            let mapped = self.mapped.clone();
+            self.readback.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+                *mapped.lock() = Some(result);
+            });
+            self.state = Readback::Mapping;
+        }
+
+        if self.state != Readback::Mapping {
+            return None;
+        }
+
+        // on the web this does nothing, and the browser calls back in its own time.
+        let _ = ctx.device.poll(wgpu::PollType::Poll);
+        match self.mapped.lock().take()? {
+            Ok(()) => {
+                let mapped = self.readback.slice(..).get_mapped_range();
+                let results = mapped
+                    .chunks_exact(wgpu::QUERY_SIZE as usize)
+                    .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+                    .collect();
+                drop(mapped);
+                self.readback.unmap();
+                self.state = Readback::Idle;
+                Some(results)
+            }
+            Err(error) => {
+                log::warn!("Failed to read back queries: {error}");
+                self.state = Readback::Idle;
+                None
+            }
+        }
+    }
+
+    /// Records copying `queries` out of `set` into `encoder`, unless the previous results are
+    /// still on their way back. Returns whether it did.
+    pub(crate) fn resolve(&mut self, encoder: &mut wgpu::CommandEncoder, set: &wgpu::QuerySet, queries: Range<u32>) -> bool {
+        if self.state != Readback::Idle || queries.is_empty() {
+            return false;
+        }
+
+        encoder.resolve_query_set(set, queries, &self.resolve, 0);
+        encoder.copy_buffer_to_buffer(&self.resolve, 0, &self.readback, 0, self.resolve.size());
+        self.state = Readback::Copied;
+        true
+    }
+}
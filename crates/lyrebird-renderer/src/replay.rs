@@ -0,0 +1,150 @@
+use std::time::Duration;
+
+use winit::{
+    dpi::PhysicalPosition,
+    event::{ElementState, Ime, MouseButton, MouseScrollDelta, WindowEvent},
+    keyboard::{Key, ModifiersState, PhysicalKey, SmolStr},
+};
+
+/// An input event the way [crate::prelude::InputManager] tracks it, without the parts of
+/// [WindowEvent] that tie it to a device or a platform, so it can be saved and fed back in.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum RecordedEvent {
+    Key {
+        physical_key: PhysicalKey,
+        logical_key: Key,
+        state: ElementState,
+        text: Option<SmolStr>,
+    },
+    CursorMoved(PhysicalPosition<f64>),
+    CursorEntered,
+    CursorLeft,
+    MouseButton { button: MouseButton, state: ElementState },
+    MouseWheel(MouseScrollDelta),
+    Modifiers(ModifiersState),
+    Focused(bool),
+    Ime(Ime),
+    /// Raw mouse motion, from a device event rather than a window event.
+    MouseMotion(f64, f64),
+}
+
+impl RecordedEvent {
+    /// The part of `event` the input manager acts on, or `None` if it ignores it.
+    pub(crate) fn from_window_event(event: &WindowEvent) -> Option<Self> {
+        Some(match event {
+            WindowEvent::KeyboardInput { event, .. } => Self::Key {
+                physical_key: event.physical_key,
+                logical_key: event.logical_key.clone(),
+                state: event.state,
+                text: event.text.clone(),
+            },
+            WindowEvent::CursorMoved { position, .. } => Self::CursorMoved(*position),
+            WindowEvent::CursorEntered { .. } => Self::CursorEntered,
+            WindowEvent::CursorLeft { .. } => Self::CursorLeft,
+            WindowEvent::MouseInput { state, button, .. } => Self::MouseButton {
+                button: *button,
+                state: *state,
+            },
+            WindowEvent::MouseWheel { delta, .. } => Self::MouseWheel(*delta),
+            WindowEvent::ModifiersChanged(modifiers) => Self::Modifiers(modifiers.state()),
+            WindowEvent::Focused(focused) => Self::Focused(*focused),
+            WindowEvent::Ime(ime) => Self::Ime(ime.clone()),
+            _ => return None,
+        })
+    }
+}
+
+/// The input of a single frame: every event that came in before its `update`, and the time
+/// step it ran with.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct RecordedFrame {
+    pub dt: Duration,
+    pub events: Vec<RecordedEvent>,
+}
+
+/// Keyboard and mouse input captured frame by frame with
+/// [crate::prelude::InputManager::start_recording], for replaying a session in a test or as a
+/// demo with [crate::prelude::InputManager::play]. It is serde-serializable, so it can be
+/// saved next to a test and replayed in CI, ideally through [crate::run_headless].
+///
+/// A replay is only as deterministic as the scene. What it reproduces is the input each
+/// `update` sees and the `dt` it is given; it doesn't cover:
+///
+/// - Gamepads. gilrs is polled for real during playback, and its events arrive whenever the
+///   controller sends them, which doesn't line up with frames from one run to the next.
+/// - Wall-clock queries, such as how long a key has been held and double-click timing, which
+///   go by when events are applied during playback rather than when they were recorded.
+/// - The window's size and scale factor, which playback leaves as they are.
+/// - [crate::prelude::InputManager::drain_events] and `take_latest_event`, which only ever hold
+///   live [WindowEvent]s. Key events can't be rebuilt from a recording.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct InputRecording {
+    pub frames: Vec<RecordedFrame>,
+}
+
+impl InputRecording {
+    /// Number of frames recorded.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// The sum of every frame's `dt`, which is how long the recording plays for.
+    pub fn duration(&self) -> Duration {
+        self.frames.iter().map(|frame| frame.dt).sum()
+    }
+}
+
+/// Whether input is being recorded or played back, see [InputRecording].
+#[derive(Default)]
+pub(crate) enum Replay {
+    #[default]
+    Live,
+    Recording {
+        recording: InputRecording,
+        /// Events of the frame that hasn't begun yet.
+        pending: Vec<RecordedEvent>,
+    },
+    Playing {
+        recording: InputRecording,
+        next_frame: usize,
+    },
+}
+
+impl Replay {
+    pub(crate) fn is_playing(&self) -> bool {
+        matches!(self, Self::Playing { .. })
+    }
+
+    /// Keeps `event` for the frame being recorded, if there is one.
+    pub(crate) fn record(&mut self, event: &RecordedEvent) {
+        if let Self::Recording { pending, .. } = self {
+            pending.push(event.clone());
+        }
+    }
+
+    /// Finishes recording a frame that ran with `dt`, or hands back the next frame to play. A
+    /// recording that has played to the end goes back to live input.
+    pub(crate) fn begin_frame(&mut self, dt: Duration) -> Option<RecordedFrame> {
+        match self {
+            Self::Live => None,
+            Self::Recording { recording, pending } => {
+                let events = std::mem::take(pending);
+                recording.frames.push(RecordedFrame { dt, events });
+                None
+            }
+            Self::Playing { recording, next_frame } => {
+                let frame = recording.frames.get(*next_frame).cloned();
+                *next_frame += 1;
+                if frame.is_none() {
+                    log::info!("Input playback finished after {} frames", recording.len());
+                    *self = Self::Live;
+                }
+                frame
+            }
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+
+use crate::GraphicsContext;
+
+/// How a texture is filtered and what happens past its edges, for
+/// [GraphicsContext::create_sampler] and [GraphicsContext::sampler].
+///
+/// Pixel art wants [Self::NEAREST_CLAMP] (or [Self::NEAREST_REPEAT] for tiles), which keeps
+/// every texel a hard-edged square however it is scaled. To keep the whole scene sharp, also
+/// pick `Nearest` in [crate::AppBuilder::upscale_filter], as that is how the viewport is
+/// stretched into the window when it renders below full resolution.
+///
+/// Textured surfaces seen at a grazing angle, such as floors in a 3D scene, blur with plain
+/// linear filtering; [Self::with_anisotropy] keeps them sharp, at some cost in bandwidth.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SamplerConfig {
+    /// Filtering when the texture is magnified or minified.
+    pub filter: wgpu::FilterMode,
+    /// Filtering between mip levels, which only matters for textures that have them.
+    pub mipmap_filter: wgpu::FilterMode,
+    /// What is sampled outside 0..1, on all three axes.
+    pub address_mode: wgpu::AddressMode,
+    /// The most samples anisotropic filtering takes, from 1 (off, the default) to
+    /// [Self::MAX_ANISOTROPY]. See [Self::with_anisotropy].
+    pub anisotropy_clamp: u16,
+}
+
+impl SamplerConfig {
+    pub const NEAREST_CLAMP: Self = Self::new(wgpu::FilterMode::Nearest, wgpu::AddressMode::ClampToEdge);
+    pub const NEAREST_REPEAT: Self = Self::new(wgpu::FilterMode::Nearest, wgpu::AddressMode::Repeat);
+    pub const LINEAR_CLAMP: Self = Self::new(wgpu::FilterMode::Linear, wgpu::AddressMode::ClampToEdge);
+    pub const LINEAR_REPEAT: Self = Self::new(wgpu::FilterMode::Linear, wgpu::AddressMode::Repeat);
+
+    /// The highest [Self::anisotropy_clamp] wgpu supports.
+    pub const MAX_ANISOTROPY: u16 = 16;
+
+    /// A sampler that uses `filter` for texels and mip levels alike.
+    pub const fn new(filter: wgpu::FilterMode, address_mode: wgpu::AddressMode) -> Self {
+        Self {
+            filter,
+            mipmap_filter: filter,
+            address_mode,
+            anisotropy_clamp: 1,
+        }
+    }
+
+    pub fn with_mipmap_filter(mut self, filter: wgpu::FilterMode) -> Self {
+        self.mipmap_filter = filter;
+        self
+    }
+
+    /// Filters with up to `clamp` anisotropic samples, typically 4, 8 or 16.
+    ///
+    /// Anisotropic filtering is an optional feature on downlevel backends (WebGL2 and some GLES
+    /// devices), and wgpu only allows it on samplers that are linear throughout. So the level is
+    /// checked when the sampler is created, and lowered with a warning when it can't be had:
+    /// capped at [Self::MAX_ANISOTROPY], and turned off for samplers with a `Nearest` filter or
+    /// on adapters without anisotropic filtering. On the web, where the adapter isn't known
+    /// (see [GraphicsContext::adapter]), wgpu turns it off on those adapters without a warning.
+    pub fn with_anisotropy(mut self, clamp: u16) -> Self {
+        self.anisotropy_clamp = clamp.max(1);
+        self
+    }
+
+    /// This config with [Self::anisotropy_clamp] lowered to what `graphics` can create.
+    fn supported(mut self, graphics: &GraphicsContext) -> Self {
+        let requested = self.anisotropy_clamp;
+        if requested <= 1 {
+            return self;
+        }
+
+        let linear = [self.filter, self.mipmap_filter]
+            .iter()
+            .all(|filter| *filter == wgpu::FilterMode::Linear);
+        let supported = graphics.adapter().is_none_or(|adapter| {
+            adapter
+                .get_downlevel_capabilities()
+                .flags
+                .contains(wgpu::DownlevelFlags::ANISOTROPIC_FILTERING)
+        });
+        self.anisotropy_clamp = match (linear, supported) {
+            (false, _) => {
+                log::warn!("Anisotropic filtering needs linear filtering throughout, turning it off for a {:?} sampler", self.filter);
+                1
+            }
+            (true, false) => {
+                log::warn!("The adapter doesn't support anisotropic filtering, turning it off");
+                1
+            }
+            (true, true) if requested > Self::MAX_ANISOTROPY => {
+                log::warn!("Anisotropy of {requested} requested, reducing it to {}", Self::MAX_ANISOTROPY);
+                Self::MAX_ANISOTROPY
+            }
+            (true, true) => requested,
+        };
+        self
+    }
+
+    fn descriptor(&self) -> wgpu::SamplerDescriptor<'static> {
+        wgpu::SamplerDescriptor {
+            label: Some("lyrebird sampler"),
+            address_mode_u: self.address_mode,
+            address_mode_v: self.address_mode,
+            address_mode_w: self.address_mode,
+            mag_filter: self.filter,
+            min_filter: self.filter,
+            mipmap_filter: self.mipmap_filter,
+            anisotropy_clamp: self.anisotropy_clamp,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for SamplerConfig {
+    /// Linear and clamped, which is what textures and sprites get unless told otherwise.
+    fn default() -> Self {
+        Self::LINEAR_CLAMP
+    }
+}
+
+/// Samplers handed out by [GraphicsContext::sampler]. There are only ever a handful, so they are
+/// kept for as long as the device.
+pub(crate) type SamplerCache = HashMap<SamplerConfig, wgpu::Sampler>;
+
+impl GraphicsContext {
+    /// Creates a new sampler from `config`. Prefer [Self::sampler] unless the sampler needs to
+    /// be a distinct object.
+    pub fn create_sampler(&self, config: SamplerConfig) -> wgpu::Sampler {
+        self.device.create_sampler(&config.supported(self).descriptor())
+    }
+
+    /// A shared sampler for `config`, created the first time it's asked for.
+    pub fn sampler(&self, config: SamplerConfig) -> wgpu::Sampler {
+        self.samplers
+            .lock()
+            .entry(config)
+            .or_insert_with(|| self.create_sampler(config))
+            .clone()
+    }
+
+    pub fn nearest_clamp(&self) -> wgpu::Sampler {
+        self.sampler(SamplerConfig::NEAREST_CLAMP)
+    }
+
+    pub fn nearest_repeat(&self) -> wgpu::Sampler {
+        self.sampler(SamplerConfig::NEAREST_REPEAT)
+    }
+
+    pub fn linear_clamp(&self) -> wgpu::Sampler {
+        self.sampler(SamplerConfig::LINEAR_CLAMP)
+    }
+
+    pub fn linear_repeat(&self) -> wgpu::Sampler {
+        self.sampler(SamplerConfig::LINEAR_REPEAT)
+    }
+}
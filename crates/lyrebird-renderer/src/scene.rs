@@ -1,18 +1,506 @@
-use std::sync::Arc;
+use std::{collections::VecDeque, sync::{Arc, atomic::{AtomicBool, Ordering}}, time::Duration};
 
-use crate::input::InputManager;
+use parking_lot::Mutex;
+use web_time::Instant;
+use winit::{dpi::{PhysicalPosition, PhysicalSize}, event::{DeviceEvent, DeviceId, WindowEvent}, window::{CursorIcon, WindowLevel}};
 
+use crate::{color::Color, encoder::{EncoderQueue, SpawnedEncoder}, input::{GamepadInfo, InputManager}, pass::{LoadPolicy, RenderPassBuilder}, profiler::{GpuProfiler, PassTiming}, stack::{Scene, SceneCommand}, timing::{FrameLimiter, LatencyProfile}, window::{FullscreenRequest, MonitorInfo, VideoMode, WindowGeometry}};
+
+/// The parts of a run every [Context] shares, kept together behind one `Arc`. They carry over a
+/// device loss.
+#[derive(Default)]
+pub(crate) struct AppShared {
+    pub(crate) limiter: FrameLimiter,
+    pub(crate) exit_requested: AtomicBool,
+    pub(crate) scene_commands: Mutex<VecDeque<SceneCommand>>,
+    pub(crate) window_requests: Mutex<WindowRequests>,
+    /// Kept up to date as the window moves and resizes.
+    pub(crate) window_geometry: Mutex<Option<WindowGeometry>>,
+    /// Re-read every so often, as winit doesn't say when monitors come and go.
+    pub(crate) monitors: Mutex<Vec<MonitorInfo>>,
+}
+
+/// Window changes asked for during a frame, applied to the window once the update is done.
+#[derive(Default)]
+pub(crate) struct WindowRequests {
+    pub(crate) ime_allowed: Option<bool>,
+    pub(crate) ime_cursor_area: Option<(PhysicalPosition<f64>, PhysicalSize<u32>)>,
+    /// The icon from [Context::set_cursor_icon], under any pushed ones.
+    pub(crate) cursor_icon: CursorIcon,
+    pub(crate) cursor_icons: Vec<CursorIcon>,
+    /// The icon the window was last given, so it's only set again when it changes.
+    pub(crate) applied_cursor_icon: Option<CursorIcon>,
+    pub(crate) fullscreen: Option<FullscreenRequest>,
+    pub(crate) window_level: Option<WindowLevel>,
+}
+
+impl WindowRequests {
+    /// The icon that should be showing: the most recently pushed one, or the base otherwise.
+    pub(crate) fn cursor_icon(&self) -> CursorIcon {
+        self.cursor_icons.last().copied().unwrap_or(self.cursor_icon)
+    }
+}
+
+#[derive(Clone)]
 pub struct Context {
     pub graphics: Arc<crate::GraphicsContext>,
     pub input: InputManager,
+    pub(crate) start: Instant,
+    pub(crate) dt: Duration,
+    pub(crate) dt_clamped: bool,
+    pub(crate) clear_color: wgpu::Color,
+    pub(crate) viewport_size: (u32, u32),
+    pub(crate) render_scale: f32,
+    pub(crate) surface_ready: bool,
+    pub(crate) viewport_format: wgpu::TextureFormat,
+    pub(crate) interpolation_alpha: f32,
+    pub(crate) shared: Arc<AppShared>,
+    pub(crate) profiler: GpuProfiler,
+    pub(crate) encoders: EncoderQueue,
+}
+
+impl Context {
+    /// Time since the app started. This is monotonic (backed by `performance.now()` on the web)
+    /// and keeps counting across focus changes and device loss.
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// [Self::elapsed] in seconds, which is handy for feeding shaders and animations.
+    pub fn elapsed_secs(&self) -> f64 {
+        self.elapsed().as_secs_f64()
+    }
+
+    /// Time between the previous frame and this one, at full precision. Zero before the first
+    /// frame, and no longer than [crate::AppBuilder::max_dt].
+    pub fn dt(&self) -> Duration {
+        self.dt
+    }
+
+    /// Whether this frame took longer than [crate::AppBuilder::max_dt] and [Self::dt] was cut
+    /// down to it. A scene can skip simulating such a frame altogether, rather than carry on
+    /// from before a stall.
+    pub fn dt_clamped(&self) -> bool {
+        self.dt_clamped
+    }
+
+    /// [Self::dt] in seconds, the same value `update` receives.
+    pub fn dt_secs(&self) -> f64 {
+        self.dt.as_secs_f64()
+    }
+
+    /// How far this frame is between the last [AppBehaviour::fixed_update] and the next, from 0
+    /// to just under 1: the time left over after the frame's fixed steps, divided by
+    /// [AppBehaviour::fixed_timestep]. It is 1 when the app has no fixed timestep.
+    ///
+    /// Rendering at the simulation's latest state judders whenever frames and steps don't line
+    /// up, so scenes with a fixed timestep should keep the state from before the last step as
+    /// well as after it (for transforms, usually a copy of both), and in [AppBehaviour::render]
+    /// draw `previous.lerp(current, alpha)`.
+    pub fn interpolation_alpha(&self) -> f32 {
+        self.interpolation_alpha
+    }
+
+    /// Caps the frame rate at `fps`, or lifts the cap with `None`. Takes effect from the next
+    /// frame, and does nothing on the web. See [FrameLimiter].
+    pub fn set_fps_cap(&self, fps: Option<u32>) {
+        self.shared.limiter.set_target(fps);
+    }
+
+    pub fn fps_cap(&self) -> Option<u32> {
+        self.shared.limiter.target()
+    }
+
+    /// Switches to the frame rate cap of `profile`. The GPU it prefers was already picked at
+    /// startup, so that part only applies through [crate::AppBuilder::latency_profile].
+    pub fn set_latency_profile(&self, profile: LatencyProfile) {
+        log::info!("Switching to the {profile:?} latency profile, fps cap {:?}", profile.fps_cap());
+        self.set_fps_cap(profile.fps_cap());
+    }
+
+    /// Asks the app to shut down once the current frame is done, such as for "press Escape to
+    /// quit". [AppBehaviour::exiting] still runs before the app goes away.
+    pub fn request_exit(&self) {
+        self.shared.exit_requested.store(true, Ordering::Release);
+    }
+
+    /// Size in physical pixels of the view passed to [AppBehaviour::render].
+    pub fn viewport_size(&self) -> (u32, u32) {
+        self.viewport_size
+    }
+
+    /// The [AppBehaviour::render_scale] of this frame, which is how many of the view's pixels
+    /// there are to each pixel on screen.
+    pub fn render_scale(&self) -> f32 {
+        self.render_scale
+    }
+
+    /// Whether the viewport the scene renders into has been created and handed to Slint. It is
+    /// `false` during [AppBehaviour::init] and the first frame's `update`, and again for the
+    /// frame after a device loss, so expensive work (or an "initializing" screen) can wait for
+    /// the first real frame. The window surface itself is Slint's, which only starts rendering
+    /// once it is configured, so by the time any [Context] exists it is ready. Headless runs
+    /// are ready from the start.
+    pub fn surface_ready(&self) -> bool {
+        self.surface_ready
+    }
+
+    /// Format of the view passed to [AppBehaviour::render], which pipelines drawing into it
+    /// need to target. This is [crate::State::FORMAT] unless [crate::AppBuilder::viewport_format]
+    /// asked for another.
+    ///
+    /// There is no way to list the window surface's own capabilities (its present modes, alpha
+    /// modes and formats) for a settings screen. Slint creates and configures the surface and
+    /// doesn't hand it out, nor offer a way to reconfigure it, so none of them could be picked
+    /// anyway. The viewport format is what a scene gets to choose.
+    pub fn viewport_format(&self) -> wgpu::TextureFormat {
+        self.viewport_format
+    }
+
+    /// A command encoder that can be recorded on another thread during [AppBehaviour::render]
+    /// and is submitted once it returns, for spreading the draw calls of heavy scenes across
+    /// threads. See [SpawnedEncoder] for how they are ordered.
+    pub fn spawn_encoder(&self, label: &str) -> SpawnedEncoder {
+        self.encoders.spawn(&self.graphics, label)
+    }
+
+    /// Times render passes on the GPU, see [GpuProfiler].
+    pub fn gpu_profiler(&self) -> &GpuProfiler {
+        &self.profiler
+    }
+
+    /// How long each pass timed with [GpuProfiler] took on the GPU, as of a frame or two ago.
+    /// Empty if the device doesn't support timestamp queries.
+    pub fn gpu_timings(&self) -> Vec<PassTiming> {
+        self.profiler.timings()
+    }
+
+    /// Pushes `scene` on top of the [crate::prelude::SceneStack] once the current update is
+    /// done. Does nothing unless the app is a scene stack.
+    pub fn push_scene(&self, scene: impl Scene + 'static) {
+        self.shared.scene_commands.lock().push_back(SceneCommand::Push(Box::new(scene)));
+    }
+
+    /// Pops the top scene off the [crate::prelude::SceneStack], exiting the app if it was the
+    /// last one.
+    pub fn pop_scene(&self) {
+        self.shared.scene_commands.lock().push_back(SceneCommand::Pop);
+    }
+
+    /// Swaps the top scene of the [crate::prelude::SceneStack] for `scene`.
+    pub fn replace_scene(&self, scene: impl Scene + 'static) {
+        self.shared.scene_commands.lock().push_back(SceneCommand::Replace(Box::new(scene)));
+    }
+
+    pub(crate) fn take_scene_command(&self) -> Option<SceneCommand> {
+        self.shared.scene_commands.lock().pop_front()
+    }
+
+    /// Turns the IME on or off for the window, for while the scene has a text field focused.
+    /// Composed text then arrives through [InputManager::ime_preedit] and
+    /// [InputManager::ime_commit] rather than as key presses.
+    ///
+    /// Slint manages the IME for its own text inputs, so this only matters for text fields the
+    /// scene draws itself. Takes effect once the current update is done.
+    pub fn set_ime_allowed(&self, allowed: bool) {
+        self.shared.window_requests.lock().ime_allowed = Some(allowed);
+    }
+
+    /// Tells the IME where the text cursor is, so its candidate window opens next to it rather
+    /// than over it. `position` and `size` are in physical pixels within the viewport.
+    pub fn set_ime_cursor_area(&self, position: PhysicalPosition<f64>, size: PhysicalSize<u32>) {
+        self.shared.window_requests.lock().ime_cursor_area = Some((position, size));
+    }
+
+    /// Where the window is and how big it is, for saving and handing to
+    /// [crate::prelude::WindowConfig::with_geometry] next time. `None` in headless runs.
+    pub fn window_geometry(&self) -> Option<WindowGeometry> {
+        *self.shared.window_geometry.lock()
+    }
+
+    /// The monitors connected to the machine, as of the last second or so. Empty in headless
+    /// runs, and on the web, where only the monitor the page is on is known.
+    pub fn available_monitors(&self) -> Vec<MonitorInfo> {
+        self.shared.monitors.lock().clone()
+    }
+
+    /// The monitor the OS calls primary. Wayland doesn't have one, so this is `None` there,
+    /// even with monitors connected.
+    pub fn primary_monitor(&self) -> Option<MonitorInfo> {
+        self.shared.monitors.lock().iter().find(|monitor| monitor.is_primary).cloned()
+    }
+
+    /// Switches `monitor` to `mode` and fills it with the window, once the frame's update is
+    /// done. Pick the mode from [MonitorInfo::video_modes], for example with
+    /// [MonitorInfo::closest_video_mode].
+    ///
+    /// Exclusive fullscreen only exists on Windows, macOS and X11. Elsewhere, and if the
+    /// monitor has been unplugged or lost the mode in the meantime, a warning is logged and the
+    /// window goes borderless fullscreen instead.
+    pub fn set_fullscreen_exclusive(&self, monitor: &MonitorInfo, mode: VideoMode) {
+        self.shared.window_requests.lock().fullscreen = Some(FullscreenRequest::Exclusive(monitor.clone(), mode));
+    }
+
+    /// Fills `monitor` with the window without changing its video mode, or the monitor the
+    /// window is on when `None`. On the web this only works from an input event handler, such
+    /// as [AppBehaviour::window_event], as browsers only allow it in response to the user.
+    pub fn set_fullscreen_borderless(&self, monitor: Option<&MonitorInfo>) {
+        self.shared.window_requests.lock().fullscreen = Some(FullscreenRequest::Borderless(monitor.cloned()));
+    }
+
+    /// Leaves either kind of fullscreen.
+    pub fn set_windowed(&self) {
+        self.shared.window_requests.lock().fullscreen = Some(FullscreenRequest::Windowed);
+    }
+
+    /// Keeps the window above or below every other window, or puts it back among them with
+    /// `WindowLevel::Normal`. Takes effect once the current update is done. The level the
+    /// window opens with is set with [crate::prelude::WindowConfig::with_window_level].
+    ///
+    /// Windows, macOS and X11 can change the level at any time. Wayland leaves stacking to the
+    /// compositor, and the web and mobile have no other windows to stack against, so there a
+    /// warning is logged and nothing changes. The window can't be recreated with the level
+    /// instead, as Slint owns it.
+    pub fn set_window_level(&self, level: WindowLevel) {
+        self.shared.window_requests.lock().window_level = Some(level);
+    }
+
+    /// [Self::set_window_level] with `AlwaysOnTop`, or `Normal` to stop.
+    pub fn set_always_on_top(&self, always_on_top: bool) {
+        self.set_window_level(if always_on_top { WindowLevel::AlwaysOnTop } else { WindowLevel::Normal });
+    }
+
+    /// Sets the mouse cursor shown over the window. Like the IME, this takes effect once the
+    /// current update is done. Platforms without a given icon fall back to a similar one, or
+    /// the default arrow.
+    ///
+    /// Slint sets the cursor too, wherever the UI asks for one with `mouse-cursor`, and
+    /// whichever of the two changed it last wins.
+    pub fn set_cursor_icon(&self, icon: CursorIcon) {
+        self.shared.window_requests.lock().cursor_icon = icon;
+    }
+
+    /// Shows `icon` on top of the current one until the matching [Self::pop_cursor_icon], for
+    /// transient states like hovering a gizmo handle.
+    pub fn push_cursor_icon(&self, icon: CursorIcon) {
+        self.shared.window_requests.lock().cursor_icons.push(icon);
+    }
+
+    /// Goes back to the cursor from before the last [Self::push_cursor_icon]. Does nothing if
+    /// nothing was pushed.
+    pub fn pop_cursor_icon(&self) {
+        self.shared.window_requests.lock().cursor_icons.pop();
+    }
+
+    /// The colour returned by [AppBehaviour::clear_color] for this frame.
+    pub fn clear_color(&self) -> wgpu::Color {
+        self.clear_color
+    }
+
+    /// Begins a render pass that clears `view` to [Self::clear_color]. Use a
+    /// [RenderPassBuilder] directly for anything more involved, such as a depth attachment.
+    pub fn begin_clear_pass<'a>(
+        &self,
+        encoder: &'a mut wgpu::CommandEncoder,
+        view: &'a wgpu::TextureView,
+    ) -> wgpu::RenderPass<'a> {
+        self.begin_pass(encoder, view, LoadPolicy::Clear)
+    }
+
+    /// Begins a render pass over `view`, clearing it or keeping what's there as `load` says.
+    /// Keeping it lets several passes layer onto one view, or a scene accumulate over frames,
+    /// see [LoadPolicy] for when that works.
+    pub fn begin_pass<'a>(
+        &self,
+        encoder: &'a mut wgpu::CommandEncoder,
+        view: &'a wgpu::TextureView,
+        load: LoadPolicy,
+    ) -> wgpu::RenderPass<'a> {
+        let builder = RenderPassBuilder::new(view);
+        let builder = match load {
+            LoadPolicy::Clear => builder.label("Clear Pass").clear(self.clear_color),
+            LoadPolicy::ClearTo(color) => builder.label("Clear Pass").clear(color),
+            LoadPolicy::Load => builder.label("Load Pass").load(),
+        };
+        builder.begin(encoder)
+    }
+}
+
+/// Where the scene is shown inside the window, in logical pixels from its top-left corner.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ViewportRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl ViewportRect {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self { x, y, width, height }
+    }
+
+    /// The top-left corner and size in physical pixels, with the size at least 1x1.
+    pub(crate) fn to_physical(self, scale_factor: f32) -> (PhysicalPosition<f64>, PhysicalSize<u32>) {
+        let origin = PhysicalPosition::new((self.x * scale_factor) as f64, (self.y * scale_factor) as f64);
+        let size = PhysicalSize::new(
+            ((self.width * scale_factor).round() as u32).max(1),
+            ((self.height * scale_factor).round() as u32).max(1),
+        );
+        (origin, size)
+    }
 }
 
 /// Defines the behaviour of an app. 
 pub trait AppBehaviour {
-    fn new() -> Self;
-    fn init(&mut self, ctx: Context);
-    fn update(&mut self, ctx: Context, dt: f64);
-    fn render(&mut self, ctx: Context, view: &wgpu::TextureView);
+    /// Whatever the app needs to be constructed with, such as a level path or a settings
+    /// struct. Use `()` if it needs nothing, which is what [crate::run] expects.
+    type Args;
+
+    fn new(args: Self::Args) -> Self;
+    fn init(&mut self, ctx: &Context);
+    fn update(&mut self, ctx: &Context, dt: f64);
+
+    /// Advances the simulation by exactly `dt` seconds, the [Self::fixed_timestep]. It runs as
+    /// many times a frame as the time since the last frame covers, so none at all on some frames
+    /// and several on others, before [Self::update]. A frame runs at most 8, dropping the rest
+    /// of a long hitch. See [Context::interpolation_alpha] for drawing between steps.
+    ///
+    /// Input deltas are per frame, so a press is seen by every step the frame runs; act on
+    /// presses in [Self::update] when that matters.
+    fn fixed_update(&mut self, _ctx: &Context, _dt: f64) {}
+
+    /// The step [Self::fixed_update] runs at, or `None` (the default) for no fixed updates.
+    /// Read once per frame.
+    fn fixed_timestep(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Draws the frame into `view`. Skipped while the viewport has no area, such as when the
+    /// window is minimised, though [Self::update] and input carry on as usual.
+    fn render(&mut self, ctx: &Context, view: &wgpu::TextureView);
+
+    /// Called at the start of every frame, once the frame's input is in and before
+    /// [Self::update], for integration layers that need a fixed point to hook into whatever the
+    /// scene itself does, such as starting a profiler frame.
+    ///
+    /// It runs even when [Self::render] is skipped for a zero-sized viewport. Slint acquires and
+    /// presents the window's surface itself, after lyrebird's part of the frame, so this is
+    /// the closest there is to "before acquire".
+    fn frame_begin(&mut self, _ctx: &Context) {}
+
+    /// Called at the end of every frame, after [Self::render], egui and the composite into the
+    /// window have been submitted and before the frame's input deltas are reset. It pairs with
+    /// [Self::frame_begin], including on frames that didn't render. Slint presents the window
+    /// right after.
+    fn frame_end(&mut self, _ctx: &Context) {}
+
+    /// The colour the viewport should be cleared to, read once per frame before `update`.
+    ///
+    /// The value is linear, see [Color] for building one from sRGB.
+    fn clear_color(&self) -> wgpu::Color {
+        Color::linear(0.1, 0.2, 0.3, 1.0).into()
+    }
+
+    /// The fraction of the viewport's resolution the scene is rendered at, read once per frame
+    /// before `update`. Below 1 the scene renders into a smaller view that is stretched back
+    /// up to fill the viewport, trading sharpness for speed on slow GPUs; above 1 it is
+    /// supersampled. See [crate::AppBuilder::upscale_filter] for how it is stretched.
+    ///
+    /// [Context::viewport_size] is the scaled size, and egui is drawn at it too. Cursor
+    /// positions stay in window pixels, so multiply them by [Context::render_scale] to get to
+    /// the view's pixels. Headless runs always render at the size they are given.
+    fn render_scale(&self) -> f32 {
+        1.0
+    }
+
+    /// Called with the image showing the rendered scene whenever it is replaced, such as on the
+    /// first frame, after a resize or after a device loss. Its contents update every frame.
+    ///
+    /// Put it in an `Image` element (usually through an `in property <image>`) to show the scene.
+    /// Slint draws elements in the order they're declared, so UI declared after the `Image` is
+    /// drawn over the scene.
+    ///
+    /// Slint sees every window event. Unless [Self::viewport_rect] narrows it down, so does the
+    /// [InputManager], whether or not the pointer is over a Slint element; claim the events
+    /// the scene shouldn't see in [Self::window_event].
+    fn viewport_image(&mut self, _image: slint::Image) {}
+
+    /// Where the component shows [Self::viewport_image], for when it's a panel rather than the
+    /// whole window. It is read every frame and for every window event, so it can simply read
+    /// the `Image` element's geometry back out of the component.
+    ///
+    /// The scene is then rendered at the panel's size, and the [InputManager] only hears about
+    /// the pointer while it is over the panel, with cursor positions relative to its top-left
+    /// corner. Presses, scrolling and key presses elsewhere are left to the UI, while releases
+    /// always get through so nothing stays held down.
+    fn viewport_rect(&self) -> Option<ViewportRect> {
+        None
+    }
+
+    /// Called for every window event before lyrebird (and Slint) handle it, including the ones
+    /// the [InputManager] doesn't track such as `Moved` or `ThemeChanged`.
+    ///
+    /// Return true to consume the event, which stops it from reaching the input manager or Slint.
+    fn window_event(&mut self, _ctx: &Context, _event: &WindowEvent) -> bool {
+        false
+    }
+
+    /// Called for every device event, which winit reports per physical device rather than per
+    /// window: raw mouse motion and buttons, raw key presses and other axes. Raw mouse motion
+    /// also goes to [InputManager::mouse_motion] either way.
+    ///
+    /// What arrives depends on the platform. Windows, X11 and the web report raw mouse motion,
+    /// buttons, wheels and keys, macOS everything but keys, and Wayland only mouse motion. Only
+    /// Windows and X11 say when devices are added or removed. Where the platform lets winit
+    /// choose, the events only come while the window is focused. Gamepads aren't winit devices
+    /// at all; they come through gilrs, see [Self::gamepad_connected].
+    fn device_event(&mut self, _ctx: &Context, _device_id: DeviceId, _event: &DeviceEvent) {}
+
+    /// Builds this frame's egui panels, which are drawn over whatever [Self::render] drew.
+    /// Events egui claims, such as clicks on its windows, don't reach the [InputManager].
+    ///
+    /// Headless runs have no window to take input from and never call this.
+    #[cfg(feature = "egui")]
+    fn ui(&mut self, _ctx: &Context, _egui: &egui::Context) {}
+
+    /// Called when a gamepad is plugged in, so the UI can show something like "Player 2
+    /// controller connected". Controllers already plugged in at startup are in the snapshot
+    /// from the first frame instead.
+    fn gamepad_connected(&mut self, _ctx: &Context, _id: gilrs::GamepadId, _info: GamepadInfo) {}
+
+    /// Called when a gamepad is unplugged. Its last state stays in the snapshot with
+    /// `is_connected` cleared.
+    fn gamepad_disconnected(&mut self, _ctx: &Context, _id: gilrs::GamepadId) {}
+
+    /// Called after the GPU device was lost and a new one has been created from the same
+    /// adapter, at the start of the next frame. Every buffer, texture and pipeline from the old
+    /// device is invalid and needs to be recreated from `ctx`; input, updates and gamepads carry
+    /// on as they were. Headless, the interrupted frame is rendered again afterwards.
+    ///
+    /// In a window, Slint's own drawing stays on the device it was handed at startup, which
+    /// wgpu can't bring back, so after a real loss the window may stop showing new frames even
+    /// though the scene keeps running. Use `GraphicsContext::simulate_device_loss`, behind the
+    /// `debug` feature, to exercise this.
+    fn device_lost(&mut self, _ctx: &Context) {}
+
+    /// Called at the end of the frame for each validation or internal error wgpu reported
+    /// during it, such as a pipeline that failed to compile or a buffer bound with the wrong
+    /// usage. The error has already been logged. Whatever it happened to, a pipeline or a
+    /// texture say, is invalid and using it only reports more errors, but everything else still
+    /// works, so the app carries on.
+    fn gpu_error(&mut self, _ctx: &Context, _error: &wgpu::Error) {}
+
+    /// Called when the GPU has run out of memory, right before the app shuts down. The device
+    /// is still around, but allocating anything new from it will most likely fail.
+    fn out_of_memory(&mut self, _ctx: &Context) {}
 
-    fn exiting(&mut self, _ctx: Context) {}
+    /// Called once as the app shuts down, while the device is still valid, so there is still
+    /// time to read back from the GPU or save anything that needs it. Whatever
+    /// [Self::update] was doing is done by then.
+    ///
+    /// On the web the event loop never returns, so this runs when the page is unloaded
+    /// (`pagehide`) instead. Browsers don't wait for anything asynchronous started in there,
+    /// such as a buffer mapping, so only what finishes straight away is sure to happen.
+    fn exiting(&mut self, _ctx: &Context) {}
 }
\ No newline at end of file
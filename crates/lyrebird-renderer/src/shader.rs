@@ -0,0 +1,176 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::{Arc, mpsc},
+};
+
+use crate::GraphicsContext;
+
+type ReloadCallback = Box<dyn FnMut(&Path, &wgpu::ShaderModule)>;
+
+/// Loads WGSL shaders from disk and, on native platforms, reloads them when the files change,
+/// so shaders can be iterated on without recompiling.
+///
+/// Shaders are checked with naga before reaching the device, so a mistake comes back as an
+/// error pointing at the file, line and column instead of a wgpu panic. A reload that fails is
+/// logged and the last good module is kept.
+///
+/// On the web there's no file watching, and loading only works where `std::fs` does.
+pub struct ShaderManager {
+    graphics: Arc<GraphicsContext>,
+    shaders: HashMap<PathBuf, wgpu::ShaderModule>,
+    on_reload: Option<ReloadCallback>,
+    changes: mpsc::Receiver<PathBuf>,
+    #[cfg(not(target_arch = "wasm32"))]
+    watcher: Option<notify::RecommendedWatcher>,
+    watched_dirs: HashSet<PathBuf>,
+}
+
+impl ShaderManager {
+    pub fn new(graphics: Arc<GraphicsContext>) -> Self {
+        let (sender, changes) = mpsc::channel();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let watcher = {
+            use notify::EventKind;
+
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let Ok(event) = event else {
+                    return;
+                };
+                if matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_)) {
+                    for path in event.paths {
+                        let _ = sender.send(path);
+                    }
+                }
+            })
+            .inspect_err(|e| log::warn!("Shader hot-reload is unavailable: {e}"))
+            .ok()
+        };
+        #[cfg(target_arch = "wasm32")]
+        drop(sender);
+
+        Self {
+            graphics,
+            shaders: HashMap::new(),
+            on_reload: None,
+            changes,
+            #[cfg(not(target_arch = "wasm32"))]
+            watcher,
+            watched_dirs: HashSet::new(),
+        }
+    }
+
+    /// Loads the shader at `path`, or returns the already loaded module, and starts watching
+    /// the file for changes.
+    pub fn load(&mut self, path: impl AsRef<Path>) -> anyhow::Result<wgpu::ShaderModule> {
+        let path = std::fs::canonicalize(path.as_ref())
+            .map_err(|e| anyhow::anyhow!("failed to find shader {}: {e}", path.as_ref().display()))?;
+        if let Some(module) = self.shaders.get(&path) {
+            return Ok(module.clone());
+        }
+
+        let module = self.compile(&path)?;
+        self.watch(&path);
+        self.shaders.insert(path, module.clone());
+        Ok(module)
+    }
+
+    /// The module loaded from `path`, if there is one.
+    pub fn get(&self, path: impl AsRef<Path>) -> Option<&wgpu::ShaderModule> {
+        let path = std::fs::canonicalize(path).ok()?;
+        self.shaders.get(&path)
+    }
+
+    /// Sets a callback to run with every shader that was reloaded, for rebuilding the
+    /// pipelines that use it.
+    pub fn on_reload(&mut self, callback: impl FnMut(&Path, &wgpu::ShaderModule) + 'static) {
+        self.on_reload = Some(Box::new(callback));
+    }
+
+    /// Reloads every shader that changed on disk since the last call, returning their paths.
+    /// Call this once per frame, such as at the start of `update`.
+    pub fn poll(&mut self) -> Vec<PathBuf> {
+        // a single save usually shows up as several events.
+        let changed: HashSet<PathBuf> = self
+            .changes
+            .try_iter()
+            .filter(|path| self.shaders.contains_key(path))
+            .collect();
+
+        let mut reloaded = Vec::new();
+        for path in changed {
+            match self.compile(&path) {
+                Ok(module) => {
+                    log::info!("Reloaded shader {}", path.display());
+                    if let Some(callback) = &mut self.on_reload {
+                        callback(&path, &module);
+                    }
+                    self.shaders.insert(path.clone(), module);
+                    reloaded.push(path);
+                }
+                Err(e) => log::error!("{e:#}"),
+            }
+        }
+        if !reloaded.is_empty() {
+            // cached pipelines still point at the old modules.
+            self.graphics.clear_pipeline_cache();
+        }
+        reloaded
+    }
+
+    fn compile(&self, path: &Path) -> anyhow::Result<wgpu::ShaderModule> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("failed to read shader {}: {e}", path.display()))?;
+        let path_str = path.display().to_string();
+
+        let module = naga::front::wgsl::parse_str(&source)
+            .map_err(|e| anyhow::anyhow!("{}", e.emit_to_string_with_path(&source, &path_str)))?;
+        naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::all())
+            .validate(&module)
+            .map_err(|e| anyhow::anyhow!("{}", e.emit_to_string_with_path(&source, &path_str)))?;
+
+        let device = &self.graphics.device;
+        let descriptor = wgpu::ShaderModuleDescriptor {
+            label: Some(&path_str),
+            source: wgpu::ShaderSource::Wgsl(source.as_str().into()),
+        };
+
+        // naga allows more than the device might, so catch what's left here, where it can be
+        // returned as an error for this file rather than reaching the app as a generic
+        // gpu_error. Popping the scope can't be blocked on in the browser.
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            device.push_error_scope(wgpu::ErrorFilter::Validation);
+            let module = device.create_shader_module(descriptor);
+            if let Some(error) = pollster::block_on(device.pop_error_scope()) {
+                anyhow::bail!("failed to create shader {path_str}: {error}");
+            }
+            Ok(module)
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            Ok(device.create_shader_module(descriptor))
+        }
+    }
+
+    /// Watches the directory holding `path`, as editors often save by replacing the file,
+    /// which a watch on the file itself wouldn't survive.
+    fn watch(&mut self, path: &Path) {
+        let Some(dir) = path.parent() else {
+            return;
+        };
+        if !self.watched_dirs.insert(dir.to_path_buf()) {
+            return;
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(watcher) = &mut self.watcher {
+            use notify::Watcher;
+
+            if let Err(e) = watcher.watch(dir, notify::RecursiveMode::NonRecursive) {
+                log::warn!("Unable to watch {} for shader changes: {e}", dir.display());
+            }
+        }
+    }
+}
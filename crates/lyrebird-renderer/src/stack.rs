@@ -0,0 +1,209 @@
+use winit::event::{DeviceEvent, DeviceId, WindowEvent};
+
+use crate::scene::{AppBehaviour, Context};
+
+/// One state of a [SceneStack], such as a menu, a level or a pause screen.
+pub trait Scene {
+    /// Called when the scene is pushed onto the stack (or replaces the one on top).
+    fn init(&mut self, ctx: &Context);
+    fn update(&mut self, ctx: &Context, dt: f64);
+    /// Scenes below an [StackMode::Overlay] or [StackMode::Passthrough] scene render first, so
+    /// a scene that sits on top of another must not clear the view.
+    fn render(&mut self, ctx: &Context, view: &wgpu::TextureView);
+
+    /// Called when another scene is pushed on top of this one.
+    fn pause(&mut self, _ctx: &Context) {}
+
+    /// Called when this scene is back on top, after the one above it was popped.
+    fn resume(&mut self, _ctx: &Context) {}
+
+    /// Called when the scene is popped or replaced, and for every scene still on the stack
+    /// when the app exits (top first).
+    fn exit(&mut self, _ctx: &Context) {}
+
+    /// Only the top scene sees window events.
+    fn window_event(&mut self, _ctx: &Context, _event: &WindowEvent) -> bool {
+        false
+    }
+
+    /// Like window events, only the top scene sees device events.
+    fn device_event(&mut self, _ctx: &Context, _device_id: DeviceId, _event: &DeviceEvent) {}
+
+    /// Called for the scenes that render, in the same order, see [AppBehaviour::ui].
+    #[cfg(feature = "egui")]
+    fn ui(&mut self, _ctx: &Context, _egui: &egui::Context) {}
+
+    /// Called for every scene on the stack, see [AppBehaviour::device_lost].
+    fn device_lost(&mut self, _ctx: &Context) {}
+
+    fn stack_mode(&self) -> StackMode {
+        StackMode::Exclusive
+    }
+}
+
+/// How a scene treats the ones below it while it is on top.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StackMode {
+    /// Only this scene updates and renders.
+    #[default]
+    Exclusive,
+    /// The scenes below keep rendering but are frozen, like the game behind a pause menu.
+    Overlay,
+    /// The scenes below keep updating and rendering, like the game under a HUD.
+    Passthrough,
+}
+
+/// A change to the stack, queued through the [Context] and applied by the [SceneStack] once
+/// the current update is done.
+pub(crate) enum SceneCommand {
+    Push(Box<dyn Scene>),
+    Pop,
+    Replace(Box<dyn Scene>),
+}
+
+/// An [AppBehaviour] that drives a stack of [Scene]s, for games that move between states such
+/// as menu → game → pause.
+///
+/// Scenes change the stack with [Context::push_scene], [Context::pop_scene] and
+/// [Context::replace_scene]. Changes queued during an update are applied in order straight
+/// after it, before anything renders:
+///
+/// - push pauses the current top scene, then initialises the new one.
+/// - pop exits the top scene, then resumes the one below. Popping the last scene exits the app.
+/// - replace exits the top scene, then initialises the new one, without pausing or resuming
+///   anything below.
+///
+/// It can be run directly with [crate::run_headless_with]. A Slint component can't own one, so
+/// a windowed app keeps it in a `thread_local!` and forwards its [AppBehaviour] methods to it.
+pub struct SceneStack {
+    scenes: Vec<Box<dyn Scene>>,
+}
+
+impl SceneStack {
+    /// Number of scenes on the stack.
+    pub fn len(&self) -> usize {
+        self.scenes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scenes.is_empty()
+    }
+
+    /// The lowest scene that still renders, given the modes of the ones above it.
+    fn visible_from(&self) -> usize {
+        let mut bottom = self.scenes.len().saturating_sub(1);
+        while bottom > 0 && self.scenes[bottom].stack_mode() != StackMode::Exclusive {
+            bottom -= 1;
+        }
+        bottom
+    }
+
+    /// The lowest scene that still updates, given the modes of the ones above it.
+    fn updating_from(&self) -> usize {
+        let mut bottom = self.scenes.len().saturating_sub(1);
+        while bottom > 0 && self.scenes[bottom].stack_mode() == StackMode::Passthrough {
+            bottom -= 1;
+        }
+        bottom
+    }
+
+    fn apply_commands(&mut self, ctx: &Context) {
+        loop {
+            // taken one at a time, as a scene's init might queue more.
+            let Some(command) = ctx.take_scene_command() else {
+                break;
+            };
+
+            match command {
+                SceneCommand::Push(mut scene) => {
+                    if let Some(top) = self.scenes.last_mut() {
+                        top.pause(ctx);
+                    }
+                    scene.init(ctx);
+                    self.scenes.push(scene);
+                }
+                SceneCommand::Pop => {
+                    if let Some(mut top) = self.scenes.pop() {
+                        top.exit(ctx);
+                    }
+                    match self.scenes.last_mut() {
+                        Some(top) => top.resume(ctx),
+                        None => ctx.request_exit(),
+                    }
+                }
+                SceneCommand::Replace(mut scene) => {
+                    if let Some(mut top) = self.scenes.pop() {
+                        top.exit(ctx);
+                    }
+                    scene.init(ctx);
+                    self.scenes.push(scene);
+                }
+            }
+        }
+    }
+}
+
+impl AppBehaviour for SceneStack {
+    /// The scene to start with.
+    type Args = Box<dyn Scene>;
+
+    fn new(initial: Box<dyn Scene>) -> Self {
+        Self {
+            scenes: vec![initial],
+        }
+    }
+
+    fn init(&mut self, ctx: &Context) {
+        if let Some(top) = self.scenes.last_mut() {
+            top.init(ctx);
+        }
+        self.apply_commands(ctx);
+    }
+
+    fn update(&mut self, ctx: &Context, dt: f64) {
+        let bottom = self.updating_from();
+        for scene in self.scenes.iter_mut().skip(bottom) {
+            scene.update(ctx, dt);
+        }
+        self.apply_commands(ctx);
+    }
+
+    fn render(&mut self, ctx: &Context, view: &wgpu::TextureView) {
+        let bottom = self.visible_from();
+        for scene in self.scenes.iter_mut().skip(bottom) {
+            scene.render(ctx, view);
+        }
+    }
+
+    #[cfg(feature = "egui")]
+    fn ui(&mut self, ctx: &Context, egui: &egui::Context) {
+        let bottom = self.visible_from();
+        for scene in self.scenes.iter_mut().skip(bottom) {
+            scene.ui(ctx, egui);
+        }
+    }
+
+    fn window_event(&mut self, ctx: &Context, event: &WindowEvent) -> bool {
+        self.scenes
+            .last_mut()
+            .is_some_and(|top| top.window_event(ctx, event))
+    }
+
+    fn device_event(&mut self, ctx: &Context, device_id: DeviceId, event: &DeviceEvent) {
+        if let Some(top) = self.scenes.last_mut() {
+            top.device_event(ctx, device_id, event);
+        }
+    }
+
+    fn device_lost(&mut self, ctx: &Context) {
+        for scene in &mut self.scenes {
+            scene.device_lost(ctx);
+        }
+    }
+
+    fn exiting(&mut self, ctx: &Context) {
+        while let Some(mut scene) = self.scenes.pop() {
+            scene.exit(ctx);
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use crate::GraphicsContext;
+
+/// Size of each staging buffer the belt allocates. Per-frame uniform and instance data is
+/// usually far below this; bigger writes get a buffer of their own.
+pub(crate) const STAGING_CHUNK_SIZE: wgpu::BufferAddress = 1 << 20;
+
+impl GraphicsContext {
+    /// Records a write of `data` into `buffer` at `offset` through a shared staging belt, which
+    /// is much cheaper than `queue.write_buffer` for lots of small per-frame updates.
+    ///
+    /// The copy lives in `encoder`, so it only takes effect once that encoder is submitted, and
+    /// it must be submitted through [Self::submit]: the belt has to be closed before the submit
+    /// and reclaimed after it, which `queue.submit` on its own doesn't do. `offset` and the
+    /// length of `data` must be multiples of [wgpu::COPY_BUFFER_ALIGNMENT], and `buffer` needs
+    /// `COPY_DST`.
+    pub fn write_buffer_staged(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        buffer: &wgpu::Buffer,
+        offset: wgpu::BufferAddress,
+        data: &[u8],
+    ) {
+        let Some(size) = wgpu::BufferSize::new(data.len() as u64) else {
+            return;
+        };
+
+        self.belt
+            .lock()
+            .write_buffer(encoder, buffer, offset, size, &self.device)
+            .copy_from_slice(data);
+    }
+
+    /// `queue.write_buffer`, padding `data` with zeroes to a whole number of
+    /// [wgpu::COPY_BUFFER_ALIGNMENT]s, which writes have to be. `buffer` needs room for the
+    /// padding.
+    pub(crate) fn write_buffer_padded(&self, buffer: &wgpu::Buffer, data: &[u8]) {
+        if (data.len() as wgpu::BufferAddress).is_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT) {
+            self.queue.write_buffer(buffer, 0, data);
+        } else {
+            let mut padded = data.to_vec();
+            padded.resize(data.len().next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT as usize), 0);
+            self.queue.write_buffer(buffer, 0, &padded);
+        }
+    }
+
+    /// Submits `encoder`, closing the staging belt beforehand and reclaiming its buffers
+    /// afterwards. Prefer this over `queue.submit` whenever
+    /// [Self::write_buffer_staged] may have been used.
+    pub fn submit(&self, encoder: wgpu::CommandEncoder) -> wgpu::SubmissionIndex {
+        self.submit_command_buffers(std::iter::once(encoder.finish()))
+    }
+
+    /// Like [Self::submit], for command buffers that have already been finished.
+    pub fn submit_command_buffers(
+        &self,
+        buffers: impl IntoIterator<Item = wgpu::CommandBuffer>,
+    ) -> wgpu::SubmissionIndex {
+        let mut belt = self.belt.lock();
+        belt.finish();
+        let index = self.queue.submit(buffers);
+        belt.recall();
+        index
+    }
+}
@@ -0,0 +1,365 @@
+use crate::GraphicsContext;
+
+/// An offscreen colour texture (with an optional depth buffer) that can be rendered into and
+/// sampled from later on, such as for post-processing, compositing or thumbnails.
+pub struct RenderTarget {
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+    has_depth: bool,
+
+    color: wgpu::Texture,
+    color_view: wgpu::TextureView,
+    depth: Option<(wgpu::Texture, wgpu::TextureView)>,
+
+    sampler: wgpu::Sampler,
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+}
+
+impl RenderTarget {
+    pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+    const USAGE: wgpu::TextureUsages = wgpu::TextureUsages::RENDER_ATTACHMENT
+        .union(wgpu::TextureUsages::TEXTURE_BINDING)
+        .union(wgpu::TextureUsages::COPY_SRC);
+
+    pub(crate) fn new(
+        ctx: &GraphicsContext,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+        has_depth: bool,
+    ) -> anyhow::Result<Self> {
+        let width = width.max(1);
+        let height = height.max(1);
+
+        let features = ctx.device.features();
+        // the same choice wgpu makes: the adapter's own format features on downlevel adapters or
+        // when the device asked for them, WebGPU's guaranteed ones otherwise.
+        let allowed = match ctx.adapter() {
+            Some(adapter)
+                if features.contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES)
+                    || !adapter.get_downlevel_capabilities().is_webgpu_compliant() =>
+            {
+                adapter.get_texture_format_features(format).allowed_usages
+            }
+            _ => format.guaranteed_format_features(features).allowed_usages,
+        };
+        let missing = Self::USAGE - allowed;
+        if !missing.is_empty() {
+            anyhow::bail!("{format:?} can't be used as a render target on this device, it lacks {missing:?}");
+        }
+        let sample_type = format
+            .sample_type(None, Some(features))
+            .ok_or_else(|| anyhow::anyhow!("{format:?} has no single sample type to bind a render target with"))?;
+
+        // float32 formats only filter with FLOAT32_FILTERABLE, and integer ones never do, so those
+        // get a non-filtering sampler, and shaders have to load from them or sample nearest.
+        let filterable = matches!(sample_type, wgpu::TextureSampleType::Float { filterable: true });
+        let filter = if filterable { wgpu::FilterMode::Linear } else { wgpu::FilterMode::Nearest };
+        let sampler = ctx.device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("render target sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: filter,
+            min_filter: filter,
+            ..Default::default()
+        });
+        let sampler_type = if filterable {
+            wgpu::SamplerBindingType::Filtering
+        } else {
+            wgpu::SamplerBindingType::NonFiltering
+        };
+
+        let bind_group_layout = ctx.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("render target bind group layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(sampler_type),
+                    count: None,
+                },
+            ],
+        });
+
+        let (color, color_view) = Self::create_color(ctx, width, height, format);
+        let depth = has_depth.then(|| Self::create_depth(ctx, width, height));
+        let bind_group = Self::create_bind_group(ctx, &bind_group_layout, &color_view, &sampler);
+
+        Ok(Self {
+            format,
+            width,
+            height,
+            has_depth,
+            color,
+            color_view,
+            depth,
+            sampler,
+            bind_group_layout,
+            bind_group,
+        })
+    }
+
+    fn create_color(
+        ctx: &GraphicsContext,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+    ) -> (wgpu::Texture, wgpu::TextureView) {
+        let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("render target colour"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: Self::USAGE,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        (texture, view)
+    }
+
+    fn create_depth(ctx: &GraphicsContext, width: u32, height: u32) -> (wgpu::Texture, wgpu::TextureView) {
+        let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("render target depth"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::DEPTH_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        (texture, view)
+    }
+
+    fn create_bind_group(
+        ctx: &GraphicsContext,
+        layout: &wgpu::BindGroupLayout,
+        view: &wgpu::TextureView,
+        sampler: &wgpu::Sampler,
+    ) -> wgpu::BindGroup {
+        ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("render target bind group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+        })
+    }
+
+    /// Recreates the textures if the requested size differs from the current one. Returns true
+    /// if the target was recreated, in which case any bind groups you built yourself are stale.
+    pub fn resize(&mut self, ctx: &GraphicsContext, width: u32, height: u32) -> bool {
+        let width = width.max(1);
+        let height = height.max(1);
+        if self.width == width && self.height == height {
+            return false;
+        }
+
+        self.width = width;
+        self.height = height;
+        (self.color, self.color_view) = Self::create_color(ctx, width, height, self.format);
+        self.depth = self.has_depth.then(|| Self::create_depth(ctx, width, height));
+        self.bind_group = Self::create_bind_group(ctx, &self.bind_group_layout, &self.color_view, &self.sampler);
+        true
+    }
+
+    /// The view to use as a colour attachment of a render pass.
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.color_view
+    }
+
+    /// The view to use as the depth attachment of a render pass, if this target has depth.
+    pub fn depth_view(&self) -> Option<&wgpu::TextureView> {
+        self.depth.as_ref().map(|(_, view)| view)
+    }
+
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.color
+    }
+
+    pub fn depth_texture(&self) -> Option<&wgpu::Texture> {
+        self.depth.as_ref().map(|(texture, _)| texture)
+    }
+
+    /// A bind group with the colour texture at binding 0 and a sampler at binding 1, for sampling
+    /// this target in a later pass. The sampler filters linearly when the format is filterable,
+    /// and is a nearest, non-filtering one otherwise (integer formats, and float32 ones without
+    /// `FLOAT32_FILTERABLE`).
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+
+    pub fn bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.bind_group_layout
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Returns true if this target can be copied straight into a texture of `format` with
+    /// `copy_texture_to_texture`, which only permits a differing sRGB suffix.
+    pub fn is_copy_compatible(&self, format: wgpu::TextureFormat) -> bool {
+        self.format.remove_srgb_suffix() == format.remove_srgb_suffix()
+    }
+
+    /// Returns true if Slint can display this target directly as an image.
+    pub fn is_slint_compatible(&self) -> bool {
+        matches!(
+            self.format,
+            wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb
+        )
+    }
+
+    /// Copies the colour texture back to the CPU, blocking until the GPU has finished.
+    ///
+    /// Rows are tightly packed in the returned buffer (the `COPY_BYTES_PER_ROW_ALIGNMENT`
+    /// padding required by the copy is stripped), in the target's own format.
+    pub fn read_pixels(&self, ctx: &GraphicsContext) -> anyhow::Result<Vec<u8>> {
+        let block_size = self
+            .format
+            .block_copy_size(None)
+            .ok_or_else(|| anyhow::anyhow!("format {:?} cannot be read back", self.format))?;
+        let unpadded_row = self.width * block_size;
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_row = unpadded_row.div_ceil(align) * align;
+
+        let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("render target readback"),
+            size: (padded_row * self.height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = ctx.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("render target readback encoder"),
+        });
+        encoder.copy_texture_to_buffer(
+            self.color.as_image_copy(),
+            wgpu::TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row),
+                    rows_per_image: Some(self.height),
+                },
+            },
+            wgpu::Extent3d {
+                width: self.width,
+                height: self.height,
+                depth_or_array_layers: 1,
+            },
+        );
+        ctx.queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        let (tx, rx) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = tx.send(result);
+        });
+        ctx.device.poll(wgpu::PollType::wait_indefinitely())?;
+        rx.recv()??;
+
+        let mapped = slice.get_mapped_range();
+        let mut pixels = Vec::with_capacity((unpadded_row * self.height) as usize);
+        for row in mapped.chunks_exact(padded_row as usize) {
+            pixels.extend_from_slice(&row[..unpadded_row as usize]);
+        }
+        drop(mapped);
+        buffer.unmap();
+
+        Ok(pixels)
+    }
+
+    /// Wraps the colour texture in a [slint::Image] so it can be composited into the UI.
+    ///
+    /// Slint only imports `Rgba8Unorm`/`Rgba8UnormSrgb` textures, so targets with any other
+    /// format need to be blitted into a compatible target first.
+    pub fn to_slint_image(&self) -> Result<slint::Image, slint::wgpu_27::TextureImportError> {
+        slint::Image::try_from(self.color.clone())
+    }
+}
+
+impl GraphicsContext {
+    /// Creates an offscreen [RenderTarget] with only a colour attachment. Fails when `format`
+    /// can't be rendered to, sampled and copied from on this device.
+    pub fn create_render_target(&self, width: u32, height: u32, format: wgpu::TextureFormat) -> anyhow::Result<RenderTarget> {
+        RenderTarget::new(self, width, height, format, false)
+    }
+
+    /// Creates an offscreen [RenderTarget] with a colour attachment and a
+    /// [RenderTarget::DEPTH_FORMAT] depth attachment.
+    pub fn create_render_target_with_depth(
+        &self,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+    ) -> anyhow::Result<RenderTarget> {
+        RenderTarget::new(self, width, height, format, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compute::ComputeContext;
+
+    #[test]
+    fn non_filterable_formats_get_matching_bindings() -> anyhow::Result<()> {
+        let compute = ComputeContext::new()?;
+        let graphics = compute.graphics();
+        for format in [wgpu::TextureFormat::R32Uint, wgpu::TextureFormat::Rgba32Float, wgpu::TextureFormat::Rgba16Float] {
+            // downlevel adapters can't render to some of these, which has to be an error rather
+            // than a validation failure.
+            let Ok(mut target) = graphics.create_render_target(8, 8, format) else {
+                continue;
+            };
+            target.resize(graphics, 16, 4);
+            assert_eq!(target.size(), (16, 4));
+        }
+        graphics.device.poll(wgpu::PollType::wait_indefinitely())?;
+        assert!(!graphics.is_lost());
+        Ok(())
+    }
+
+    #[test]
+    fn unrenderable_formats_are_an_error() -> anyhow::Result<()> {
+        let compute = ComputeContext::new()?;
+        assert!(compute.graphics().create_render_target(8, 8, wgpu::TextureFormat::Rgb9e5Ufloat).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,227 @@
+use crate::{GraphicsContext, mipmap::MipLevels, sampler::SamplerConfig};
+
+/// A sampled 2D texture with a default view and sampler, such as one loaded from an image.
+pub struct Texture {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    sampler: wgpu::Sampler,
+}
+
+impl Texture {
+    /// Uploads tightly packed RGBA8 pixels as an sRGB texture, which is what colour images are
+    /// stored as.
+    pub fn from_rgba8(
+        graphics: &GraphicsContext,
+        width: u32,
+        height: u32,
+        rgba: &[u8],
+        label: &str,
+    ) -> anyhow::Result<Self> {
+        Self::upload(graphics, wgpu::TextureFormat::Rgba8UnormSrgb, width, height, rgba, label)
+    }
+
+    /// Uploads tightly packed 8-bit single channel data as a linear `R8Unorm` texture, for
+    /// masks, heightmaps and the like. The value is sampled from the red channel.
+    pub fn from_luma8(
+        graphics: &GraphicsContext,
+        width: u32,
+        height: u32,
+        luma: &[u8],
+        label: &str,
+    ) -> anyhow::Result<Self> {
+        Self::upload(graphics, wgpu::TextureFormat::R8Unorm, width, height, luma, label)
+    }
+
+    /// Decodes an encoded image (PNG or JPEG) into an sRGB texture. Every source format,
+    /// including grayscale, is expanded to RGBA8 so it samples as the image looks; use
+    /// [Self::from_luma8] for single channel data.
+    #[cfg(feature = "image")]
+    pub fn from_bytes(graphics: &GraphicsContext, bytes: &[u8], label: &str) -> anyhow::Result<Self> {
+        Self::from_image(graphics, image::load_from_memory(bytes)?, label)
+    }
+
+    /// Decodes an image file (PNG or JPEG) like [Self::from_bytes], labelled with its path.
+    #[cfg(feature = "image")]
+    pub fn from_path(graphics: &GraphicsContext, path: impl AsRef<std::path::Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        Self::from_image(graphics, image::open(path)?, &path.display().to_string())
+    }
+
+    #[cfg(feature = "image")]
+    fn from_image(graphics: &GraphicsContext, image: image::DynamicImage, label: &str) -> anyhow::Result<Self> {
+        let image = image.into_rgba8();
+        let (width, height) = image.dimensions();
+        Self::from_rgba8(graphics, width, height, image.as_raw(), label)
+    }
+
+    fn upload(
+        graphics: &GraphicsContext,
+        format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+        data: &[u8],
+        label: &str,
+    ) -> anyhow::Result<Self> {
+        let bytes_per_pixel = format.block_copy_size(None).unwrap_or(4);
+        let bytes_per_row = width * bytes_per_pixel;
+        anyhow::ensure!(
+            data.len() == bytes_per_row as usize * height as usize,
+            "expected {} bytes of {format:?} for a {width}x{height} texture, got {}",
+            bytes_per_row as usize * height as usize,
+            data.len()
+        );
+
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let texture = graphics.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            // copied from by `with_mipmaps`.
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+
+        // unlike buffer-to-texture copies, `write_texture` takes rows of any length, so the
+        // pixels don't need padding out to 256 bytes.
+        graphics.queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            data,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(bytes_per_row),
+                rows_per_image: Some(height),
+            },
+            size,
+        );
+
+        Ok(Self::from_texture(graphics, texture))
+    }
+
+    /// Wraps an existing texture, creating the default view and using the shared linear,
+    /// edge-clamped sampler.
+    pub fn from_texture(graphics: &GraphicsContext, texture: wgpu::Texture) -> Self {
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        Self { texture, view, sampler: graphics.linear_clamp() }
+    }
+
+    /// Samples the texture with `config` instead, such as [SamplerConfig::NEAREST_CLAMP] for
+    /// pixel art.
+    pub fn with_sampler(mut self, graphics: &GraphicsContext, config: SamplerConfig) -> Self {
+        self.sampler = graphics.sampler(config);
+        self
+    }
+
+    /// Gives the texture mipmaps, so it doesn't shimmer and alias when drawn smaller than it
+    /// is. The texture is copied into a new one with `levels` mip levels, which are filled from
+    /// the full-size image with [GraphicsContext::generate_mipmaps]. The sampler is kept, and
+    /// the default ones already blend between levels.
+    ///
+    /// Textures from the loading functions above can always be given mipmaps. A texture
+    /// wrapped with [Self::from_texture] needs `COPY_SRC` usage and a renderable, filterable
+    /// format.
+    pub fn with_mipmaps(self, graphics: &GraphicsContext, levels: MipLevels) -> anyhow::Result<Self> {
+        let (width, height) = self.size();
+        let mip_level_count = levels.count(width, height);
+        if mip_level_count == 1 && self.mip_level_count() == 1 {
+            return Ok(self);
+        }
+        anyhow::ensure!(
+            self.texture.usage().contains(wgpu::TextureUsages::COPY_SRC),
+            "adding mipmaps copies the texture, which needs COPY_SRC usage"
+        );
+        // checked before the copy is created, as it can only be a render attachment in a
+        // renderable format.
+        graphics.ensure_mipmappable(self.format())?;
+
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let texture = graphics.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("mipmapped texture"),
+            size,
+            mip_level_count,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.format(),
+            usage: self.texture.usage() | wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+
+        let mut encoder = graphics.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Mipmap Copy Encoder"),
+        });
+        encoder.copy_texture_to_texture(self.texture.as_image_copy(), texture.as_image_copy(), size);
+        graphics.submit(encoder);
+        graphics.generate_mipmaps(&texture)?;
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        Ok(Self { texture, view, sampler: self.sampler })
+    }
+
+    /// How many mip levels the texture has, 1 unless it was given mipmaps.
+    pub fn mip_level_count(&self) -> u32 {
+        self.texture.mip_level_count()
+    }
+
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+
+    pub fn sampler(&self) -> &wgpu::Sampler {
+        &self.sampler
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.texture.width(), self.texture.height())
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.texture.format()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{compute::ComputeContext, mipmap::MipLevels};
+
+    use super::Texture;
+
+    #[test]
+    fn unrenderable_formats_cant_get_mipmaps() -> anyhow::Result<()> {
+        let compute = ComputeContext::new()?;
+        let graphics = compute.graphics();
+        let texture = graphics.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("shared exponent texture"),
+            size: wgpu::Extent3d { width: 8, height: 8, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgb9e5Ufloat,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+
+        assert!(Texture::from_texture(graphics, texture).with_mipmaps(graphics, MipLevels::Full).is_err());
+        graphics.device.poll(wgpu::PollType::wait_indefinitely())?;
+        assert!(graphics.take_errors().is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,123 @@
+use std::{sync::atomic::{AtomicU32, Ordering}, time::Duration};
+
+use web_time::Instant;
+
+/// Caps how many frames are drawn per second, so continuous rendering doesn't spin the GPU
+/// (and the fans) harder than it needs to.
+///
+/// Rather than sleeping, the next redraw is scheduled once the frame's budget has passed, which
+/// keeps the event loop responsive in between. On the web frames are paced by
+/// `requestAnimationFrame` instead, so the cap is ignored there.
+///
+/// This is the only pacing knob lyrebird has. The swapchain's present mode and
+/// `desired_maximum_frame_latency` (wgpu's default of 2 frames) are set by Slint when it
+/// configures the window surface, and it doesn't let them be changed afterwards.
+#[derive(Debug, Default)]
+pub struct FrameLimiter {
+    /// Target frames per second, 0 meaning uncapped.
+    target: AtomicU32,
+}
+
+impl FrameLimiter {
+    pub fn new(target: Option<u32>) -> Self {
+        let limiter = Self::default();
+        limiter.set_target(target);
+        limiter
+    }
+
+    /// Sets the target frame rate, or `None` to render as fast as possible.
+    pub fn set_target(&self, target: Option<u32>) {
+        self.target.store(target.unwrap_or(0), Ordering::Relaxed);
+    }
+
+    pub fn target(&self) -> Option<u32> {
+        match self.target.load(Ordering::Relaxed) {
+            0 => None,
+            fps => Some(fps),
+        }
+    }
+
+    /// How long a single frame may take at the target frame rate.
+    pub fn frame_budget(&self) -> Option<Duration> {
+        self.target().map(|fps| Duration::from_secs_f64(1.0 / fps as f64))
+    }
+
+    /// How long to wait before starting the next frame, given when the current one started.
+    pub(crate) fn remaining(&self, frame_start: Instant) -> Option<Duration> {
+        if cfg!(target_arch = "wasm32") {
+            return None;
+        }
+
+        self.frame_budget()
+            .and_then(|budget| budget.checked_sub(frame_start.elapsed()))
+            .filter(|remaining| !remaining.is_zero())
+    }
+}
+
+/// A preset for the pacing settings lyrebird controls, picked with
+/// [crate::AppBuilder::latency_profile] and switched at runtime with
+/// [crate::prelude::Context::set_latency_profile].
+///
+/// The settings that matter most for latency aren't among them: Slint configures the window
+/// surface with `AutoVsync` and wgpu's default frame latency of 2, and offers no way to change
+/// either. So no profile turns vsync off (a tear test won't ever tear), which would need
+/// `Immediate` or `Mailbox`. What a profile sets is the [FrameLimiter] cap and, at startup, which
+/// GPU to prefer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LatencyProfile {
+    /// No frame rate cap and the high-performance GPU, so frames are ready as soon as the
+    /// display takes them.
+    LowLatency,
+    /// No frame rate cap and wgpu's default GPU preference, the same as picking no profile.
+    #[default]
+    Balanced,
+    /// Capped at 30 fps on the low-power GPU, for laptops running on battery.
+    PowerSaver,
+}
+
+impl LatencyProfile {
+    pub fn fps_cap(self) -> Option<u32> {
+        match self {
+            Self::LowLatency | Self::Balanced => None,
+            Self::PowerSaver => Some(30),
+        }
+    }
+
+    pub fn power_preference(self) -> wgpu::PowerPreference {
+        match self {
+            Self::LowLatency => wgpu::PowerPreference::HighPerformance,
+            Self::Balanced => wgpu::PowerPreference::default(),
+            Self::PowerSaver => wgpu::PowerPreference::LowPower,
+        }
+    }
+}
+
+/// How the event loop waits for something to do, underneath the [RedrawMode].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PollStrategy {
+    /// Sleep until there's an event, a timer or a redraw to handle. This is what Slint does by
+    /// default, and with [RedrawMode::Continuous] the redraw after each frame keeps it busy.
+    #[default]
+    Wait,
+    /// Never sleep, going round the loop again as soon as it's done. Input is seen the moment
+    /// it arrives at the price of a core kept permanently busy, even while no frames are drawn.
+    Poll,
+    /// Wake up and draw a frame every so often, for tools that show something changing slowly,
+    /// like a clock or a log, without a frame per vsync. Frames are drawn once per interval
+    /// rather than one after the other, though events still get redraws in between under
+    /// [RedrawMode::OnDemand].
+    Interval(Duration),
+}
+
+/// When frames are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RedrawMode {
+    /// Draw frame after frame, paced by the [FrameLimiter]. Games and anything animated want
+    /// this.
+    #[default]
+    Continuous,
+    /// Only draw when Slint needs to, such as after input or a UI property changing. Tools that
+    /// sit idle most of the time use far less power this way. Gamepads are still checked in
+    /// between, and a frame is drawn whenever they report something.
+    OnDemand,
+}
@@ -0,0 +1,86 @@
+use std::{marker::PhantomData, num::NonZeroU64};
+
+use crate::GraphicsContext;
+
+/// Uniform buffers are sized in multiples of this, the alignment of a WGSL struct in the
+/// uniform address space.
+const UNIFORM_SIZE_ALIGNMENT: wgpu::BufferAddress = 16;
+
+/// A uniform buffer holding a single `T`, sized and padded the way WGSL expects.
+///
+/// `T` has to be `#[repr(C)]` and derive `bytemuck::Pod` and `bytemuck::Zeroable`, so its bytes
+/// can go to the GPU as they are. Its fields must also line up with the struct in the shader,
+/// which Rust won't do for you: WGSL aligns `vec3<f32>`, `vec4<f32>` and `mat4x4<f32>` to 16
+/// bytes and `vec2<f32>` to 8, so a `Vec3` followed by an `f32` packs fine, but a `Vec3`
+/// followed by a `Vec3` needs an `f32` of padding in between. The end of the buffer is padded
+/// for you.
+pub struct UniformBuffer<T: bytemuck::Pod> {
+    buffer: wgpu::Buffer,
+    _value: PhantomData<T>,
+}
+
+impl<T: bytemuck::Pod> UniformBuffer<T> {
+    /// Creates the buffer, zeroed until the first [Self::write].
+    ///
+    /// Panics if `T` is empty or bigger than the device's `max_uniform_buffer_binding_size`
+    /// (64 KiB by default), which needs a storage buffer instead.
+    pub fn new(ctx: &GraphicsContext, label: &str) -> Self {
+        let max = ctx.device.limits().max_uniform_buffer_binding_size as usize;
+        assert!(size_of::<T>() > 0, "uniform {label} is empty");
+        assert!(
+            size_of::<T>() <= max,
+            "uniform {label} is {} bytes, but the device only allows {max}",
+            size_of::<T>()
+        );
+
+        let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size: Self::SIZE,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            buffer,
+            _value: PhantomData,
+        }
+    }
+
+    /// Size of the buffer, which is `T` rounded up to a multiple of 16 bytes.
+    pub const SIZE: wgpu::BufferAddress =
+        (size_of::<T>() as wgpu::BufferAddress).div_ceil(UNIFORM_SIZE_ALIGNMENT) * UNIFORM_SIZE_ALIGNMENT;
+
+    /// Writes `value` to the buffer. Like `queue.write_buffer`, it lands before the next
+    /// submission.
+    pub fn write(&self, ctx: &GraphicsContext, value: &T) {
+        // the buffer is rounded up to 16 bytes, leaving room for any padding.
+        ctx.write_buffer_padded(&self.buffer, bytemuck::bytes_of(value));
+    }
+
+    pub fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+
+    /// The bind group entry binding the whole buffer at `binding`.
+    pub fn binding(&self, binding: u32) -> wgpu::BindGroupEntry<'_> {
+        wgpu::BindGroupEntry {
+            binding,
+            resource: self.buffer.as_entire_binding(),
+        }
+    }
+
+    /// The matching bind group layout entry, with `min_binding_size` set so a buffer of the
+    /// wrong size is caught when the bind group is created rather than at draw time.
+    pub fn layout_entry(binding: u32, visibility: wgpu::ShaderStages) -> wgpu::BindGroupLayoutEntry {
+        wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: NonZeroU64::new(Self::SIZE),
+            },
+            count: None,
+        }
+    }
+}
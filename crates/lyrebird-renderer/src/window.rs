@@ -0,0 +1,464 @@
+use std::cell::Cell;
+
+use winit::{dpi::{LogicalSize, PhysicalPosition, PhysicalSize}, monitor::{MonitorHandle, VideoModeHandle}, window::{Fullscreen, Icon, Window, WindowAttributes, WindowLevel}};
+
+/// An icon for the titlebar/taskbar, decoded into RGBA8.
+#[derive(Debug, Clone)]
+pub struct WindowIcon(Icon);
+
+impl WindowIcon {
+    /// Creates an icon from tightly packed RGBA8 pixels.
+    pub fn from_rgba(rgba: Vec<u8>, width: u32, height: u32) -> anyhow::Result<Self> {
+        Ok(Self(Icon::from_rgba(rgba, width, height)?))
+    }
+
+    /// Decodes an image file (PNG or JPEG) into an icon.
+    #[cfg(feature = "image")]
+    pub fn from_path(path: impl AsRef<std::path::Path>) -> anyhow::Result<Self> {
+        let image = image::open(path)?.into_rgba8();
+        let (width, height) = image.dimensions();
+        Self::from_rgba(image.into_raw(), width, height)
+    }
+}
+
+/// Where the window is and how big, for saving when the app exits and handing to
+/// [WindowConfig::with_geometry] the next time it starts, so it reopens where the user left it.
+/// Read it from [crate::prelude::Context::window_geometry], for example in
+/// [crate::prelude::AppBehaviour::exiting].
+///
+/// Everything is in physical pixels. While the window is maximised or minimised, the position
+/// and size are the ones it had before, so it comes back at that size when unmaximised.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct WindowGeometry {
+    /// The top-left corner of the window including its decorations, or `None` on platforms
+    /// that don't let apps place windows, such as Wayland and the web.
+    pub position: Option<(i32, i32)>,
+    /// The size of the window's contents.
+    pub size: (u32, u32),
+    pub maximized: bool,
+}
+
+impl WindowGeometry {
+    /// Reads the geometry of `window`, holding on to the position and size from `previous`
+    /// while it is maximised or minimised.
+    pub(crate) fn read(window: &Window, previous: Option<Self>) -> Self {
+        let maximized = window.is_maximized();
+        if let Some(previous) = previous.filter(|_| maximized || window.is_minimized() == Some(true)) {
+            return Self { maximized, ..previous };
+        }
+
+        let size = window.inner_size();
+        Self {
+            position: window.outer_position().ok().map(|position| (position.x, position.y)),
+            size: (size.width, size.height),
+            maximized,
+        }
+    }
+}
+
+/// A monitor connected to the machine, see [crate::prelude::Context::available_monitors].
+///
+/// Positions and sizes are in physical pixels on the virtual desktop that spans every
+/// monitor, so they can be compared with [WindowGeometry].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorInfo {
+    /// A human-readable name, when the platform has one.
+    pub name: Option<String>,
+    /// The top-left corner of the monitor. Wayland doesn't tell, so it is `(0, 0)` there.
+    pub position: (i32, i32),
+    pub size: (u32, u32),
+    pub scale_factor: f64,
+    /// The refresh rate of the current video mode in millihertz, so 59.94Hz is `59940`.
+    pub refresh_rate_millihertz: Option<u32>,
+    /// Whether the OS considers it the primary monitor, see
+    /// [crate::prelude::Context::primary_monitor].
+    pub is_primary: bool,
+    /// The modes it can be switched to in exclusive fullscreen, see
+    /// [crate::prelude::Context::set_fullscreen_exclusive]. Empty where there's no exclusive
+    /// fullscreen, such as on the web.
+    pub video_modes: Vec<VideoMode>,
+}
+
+/// A resolution, colour depth and refresh rate a monitor can be switched to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct VideoMode {
+    pub size: (u32, u32),
+    pub bit_depth: u16,
+    pub refresh_rate_millihertz: u32,
+}
+
+impl VideoMode {
+    fn new(mode: &VideoModeHandle) -> Self {
+        let size = mode.size();
+        Self {
+            size: (size.width, size.height),
+            bit_depth: mode.bit_depth(),
+            refresh_rate_millihertz: mode.refresh_rate_millihertz(),
+        }
+    }
+
+    pub fn refresh_rate_hz(&self) -> f64 {
+        self.refresh_rate_millihertz as f64 / 1000.0
+    }
+}
+
+/// How the window should fill the screen, queued by the fullscreen methods on
+/// [crate::prelude::Context].
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum FullscreenRequest {
+    Windowed,
+    Borderless(Option<MonitorInfo>),
+    Exclusive(MonitorInfo, VideoMode),
+}
+
+impl FullscreenRequest {
+    /// Sets `window` to what was asked for. Where exclusive fullscreen doesn't exist, that is
+    /// Wayland, the web and mobile, or the monitor no longer has the mode, it goes borderless
+    /// on that monitor instead.
+    pub(crate) fn apply(&self, window: &Window) {
+        let fullscreen = match self {
+            Self::Windowed => None,
+            Self::Borderless(monitor) => Some(Fullscreen::Borderless(monitor.as_ref().and_then(|monitor| monitor.find(window)))),
+            Self::Exclusive(monitor, mode) => {
+                let handle = monitor.find(window);
+                let exclusive = is_desktop_winit(window)
+                    .then(|| handle.as_ref()?.video_modes().find(|handle| VideoMode::new(handle) == *mode))
+                    .flatten();
+                match exclusive {
+                    Some(exclusive) => Some(Fullscreen::Exclusive(exclusive)),
+                    None => {
+                        log::warn!("Exclusive fullscreen at {mode:?} isn't available here, going borderless instead");
+                        Some(Fullscreen::Borderless(handle))
+                    }
+                }
+            }
+        };
+        window.set_fullscreen(fullscreen);
+    }
+}
+
+/// Whether `window` is a desktop window other than Wayland's, the only kind that can switch
+/// video modes or pick its level. Wayland quietly ignores exclusive fullscreen and leaves
+/// stacking entirely to the compositor, and a web page or a mobile app has neither.
+fn is_desktop_winit(window: &Window) -> bool {
+    !cfg!(any(target_arch = "wasm32", target_os = "android", target_os = "ios")) && !is_wayland(window)
+}
+
+fn is_wayland(window: &Window) -> bool {
+    use winit::raw_window_handle::{HasWindowHandle, RawWindowHandle};
+
+    window
+        .window_handle()
+        .is_ok_and(|handle| matches!(handle.as_raw(), RawWindowHandle::Wayland(_)))
+}
+
+/// Moves `window` to `level`, see [crate::prelude::Context::set_window_level].
+pub(crate) fn set_window_level(window: &Window, level: WindowLevel) {
+    if !is_desktop_winit(window) {
+        log::warn!("Window levels aren't supported on this platform, ignoring {level:?}");
+        return;
+    }
+    window.set_window_level(level);
+}
+
+impl MonitorInfo {
+    pub(crate) fn new(monitor: &MonitorHandle, primary: Option<&MonitorHandle>) -> Self {
+        let position = monitor.position();
+        let size = monitor.size();
+        Self {
+            name: monitor.name(),
+            position: (position.x, position.y),
+            size: (size.width, size.height),
+            scale_factor: monitor.scale_factor(),
+            refresh_rate_millihertz: monitor.refresh_rate_millihertz(),
+            is_primary: primary == Some(monitor),
+            video_modes: monitor.video_modes().map(|mode| VideoMode::new(&mode)).collect(),
+        }
+    }
+
+    /// The handle for this monitor among the ones `window` can see now, matched on name and
+    /// position, or name alone if it has moved on the desktop since.
+    fn find(&self, window: &Window) -> Option<MonitorHandle> {
+        let position = |monitor: &MonitorHandle| {
+            let position = monitor.position();
+            (position.x, position.y)
+        };
+        let named: Vec<_> = window.available_monitors().filter(|monitor| monitor.name() == self.name).collect();
+        named
+            .iter()
+            .find(|monitor| position(monitor) == self.position)
+            .or(named.first())
+            .cloned()
+    }
+
+    /// The video mode closest to `width`x`height`, and to `refresh_hz` if given, for picking a
+    /// mode to hand to [crate::prelude::Context::set_fullscreen_exclusive] from a settings menu.
+    /// Size counts first, then refresh rate, with ties going to the deeper colour.
+    pub fn closest_video_mode(&self, width: u32, height: u32, refresh_hz: Option<f64>) -> Option<VideoMode> {
+        self.video_modes.iter().copied().min_by_key(|mode| {
+            let size = mode.size.0.abs_diff(width) as u64 + mode.size.1.abs_diff(height) as u64;
+            let refresh = refresh_hz.map_or(0, |hz| mode.refresh_rate_millihertz.abs_diff((hz * 1000.0).round() as u32));
+            (size, refresh, std::cmp::Reverse(mode.bit_depth))
+        })
+    }
+
+    /// Every monitor `window` can see, in the order the platform lists them.
+    pub(crate) fn read_all(window: &Window) -> Vec<Self> {
+        let primary = window.primary_monitor();
+        window
+            .available_monitors()
+            .map(|monitor| Self::new(&monitor, primary.as_ref()))
+            .collect()
+    }
+
+    /// The refresh rate in hertz.
+    pub fn refresh_rate_hz(&self) -> Option<f64> {
+        self.refresh_rate_millihertz.map(|millihertz| millihertz as f64 / 1000.0)
+    }
+
+    /// Whether the physical point `(x, y)` is on this monitor.
+    pub fn contains(&self, (x, y): (i32, i32)) -> bool {
+        (self.position.0..self.position.0 + self.size.0 as i32).contains(&x)
+            && (self.position.1..self.position.1 + self.size.1 as i32).contains(&y)
+    }
+}
+
+/// Describes how the app's window should be created.
+#[derive(Debug, Clone)]
+pub struct WindowConfig {
+    icon: Option<WindowIcon>,
+    transparent: bool,
+    min_size: Option<LogicalSize<f64>>,
+    max_size: Option<LogicalSize<f64>>,
+    resizable: bool,
+    geometry: Option<WindowGeometry>,
+    level: WindowLevel,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self {
+            icon: None,
+            transparent: false,
+            min_size: None,
+            max_size: None,
+            resizable: true,
+            geometry: None,
+            level: WindowLevel::Normal,
+        }
+    }
+}
+
+impl WindowConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the titlebar/taskbar icon.
+    ///
+    /// This is honoured on Windows and X11. macOS takes the icon from the app bundle, Wayland
+    /// from the desktop entry and the web from the page's favicon, so it is ignored there.
+    pub fn with_icon(mut self, icon: WindowIcon) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    /// Makes the window see-through wherever nothing opaque is drawn, for overlays and HUD
+    /// tools. This also removes the decorations, which would otherwise be the only thing left
+    /// visible around a fully transparent window.
+    ///
+    /// For the desktop to show through, the Slint component needs `background: transparent`,
+    /// and [crate::prelude::AppBehaviour::clear_color] needs an alpha below 1. Slint owns and
+    /// configures the window surface, so the compositing alpha mode is whatever the surface
+    /// defaults to. Platforms that only support opaque surfaces (including some X11 setups
+    /// without a compositor) show black instead, and there's no way to detect that from here.
+    pub fn with_transparent(mut self, transparent: bool) -> Self {
+        self.transparent = transparent;
+        self
+    }
+
+    /// The smallest the window may be, in logical pixels, on top of the limits Slint takes from
+    /// the component's layout. It is set when the window is created and checked again on every
+    /// resize, as Slint updates the window's limits whenever the layout changes.
+    ///
+    /// Some compositors, such as tiling window managers, size windows however they like. If
+    /// the window ends up smaller anyway, it is asked to snap back and the scene keeps
+    /// rendering at the minimum size in the meantime, scaled down to fit.
+    pub fn with_min_size(mut self, width: f64, height: f64) -> Self {
+        self.min_size = Some(LogicalSize::new(width, height));
+        self
+    }
+
+    /// The largest the window may be, in logical pixels, enforced like [Self::with_min_size].
+    pub fn with_max_size(mut self, width: f64, height: f64) -> Self {
+        self.max_size = Some(LogicalSize::new(width, height));
+        self
+    }
+
+    /// Whether the user can resize the window. It is by default, unless the component's layout
+    /// fixes its size.
+    pub fn with_resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+
+    /// Opens the window with a [WindowGeometry] saved from an earlier run, instead of the size
+    /// the component's layout asks for. If the saved position is no longer on any monitor, say
+    /// because that monitor was unplugged, the window is centred on the primary one instead.
+    pub fn with_geometry(mut self, geometry: WindowGeometry) -> Self {
+        self.geometry = Some(geometry);
+        self
+    }
+
+    /// Opens the window above (or below) all the others, for overlays and always-visible tools.
+    /// It can be changed later with [crate::prelude::Context::set_window_level]. Windows, macOS
+    /// and X11 honour this; Wayland, the web and mobile don't have window levels and ignore it.
+    pub fn with_window_level(mut self, level: WindowLevel) -> Self {
+        self.level = level;
+        self
+    }
+
+    pub(crate) fn geometry(&self) -> Option<WindowGeometry> {
+        self.geometry
+    }
+
+    /// Moves a window placed by [Self::with_geometry] onto the primary monitor when its centre
+    /// isn't on any of the monitors there are now.
+    pub(crate) fn keep_on_screen(&self, window: &Window) {
+        if self.geometry.and_then(|geometry| geometry.position).is_none() {
+            return;
+        }
+        let Ok(position) = window.outer_position() else {
+            return;
+        };
+
+        let size = window.outer_size();
+        let centre = (position.x + size.width as i32 / 2, position.y + size.height as i32 / 2);
+        if MonitorInfo::read_all(window).iter().any(|monitor| monitor.contains(centre)) {
+            return;
+        }
+
+        let Some(monitor) = window.primary_monitor().or_else(|| window.available_monitors().next()) else {
+            return;
+        };
+        let origin = monitor.position();
+        let extent = monitor.size();
+        let x = origin.x + (extent.width as i32 - size.width as i32) / 2;
+        let y = origin.y + (extent.height as i32 - size.height as i32) / 2;
+        log::info!("The saved window position is off screen, centring it on {}", monitor.name().unwrap_or_default());
+        window.set_outer_position(PhysicalPosition::new(x, y));
+    }
+
+    /// Clamps a window size to the configured minimum and maximum. Zero sizes, which is what a
+    /// minimised window reports, are returned as they are.
+    pub(crate) fn clamp_size(&self, size: PhysicalSize<u32>, scale_factor: f64) -> PhysicalSize<u32> {
+        if size.width == 0 || size.height == 0 {
+            return size;
+        }
+
+        let mut width = size.width;
+        let mut height = size.height;
+        if let Some(max) = self.max_size.map(|max| max.to_physical::<u32>(scale_factor)) {
+            width = width.min(max.width);
+            height = height.min(max.height);
+        }
+        if let Some(min) = self.min_size.map(|min| min.to_physical::<u32>(scale_factor)) {
+            width = width.max(min.width);
+            height = height.max(min.height);
+        }
+        PhysicalSize::new(width, height)
+    }
+
+    /// Applies this config on top of the attributes Slint derived from the component.
+    pub(crate) fn apply(&self, mut attributes: WindowAttributes) -> WindowAttributes {
+        if let Some(icon) = &self.icon {
+            if cfg!(any(target_os = "macos", target_arch = "wasm32")) {
+                log::info!("Window icons are not supported on this platform, ignoring");
+            }
+            attributes = attributes.with_window_icon(Some(icon.0.clone()));
+        }
+
+        if self.transparent {
+            attributes = attributes.with_transparent(true).with_decorations(false);
+        }
+
+        if let Some(min) = self.min_size {
+            attributes = attributes.with_min_inner_size(min);
+        }
+        if let Some(max) = self.max_size {
+            attributes = attributes.with_max_inner_size(max);
+        }
+        if !self.resizable {
+            attributes = attributes.with_resizable(false);
+        }
+
+        if self.level != WindowLevel::Normal {
+            attributes = attributes.with_window_level(self.level);
+        }
+
+        if let Some(geometry) = self.geometry {
+            let (width, height) = geometry.size;
+            attributes = attributes
+                .with_inner_size(PhysicalSize::new(width, height))
+                .with_maximized(geometry.maximized);
+            if let Some((x, y)) = geometry.position {
+                attributes = attributes.with_position(PhysicalPosition::new(x, y));
+            }
+        }
+
+        attributes
+    }
+}
+
+/// The size a window resized outside its [WindowConfig] limits should snap back to. Dragging a
+/// window edge sends a flood of resizes and only the last one matters, so this keeps just that
+/// one and it is asked for once per frame rather than once per event.
+#[derive(Debug, Default)]
+pub(crate) struct PendingResize(Cell<Option<PhysicalSize<u32>>>);
+
+impl PendingResize {
+    /// Notes that the window was resized to `size`, replacing any resize not yet applied.
+    pub(crate) fn resized(&self, limits: &WindowConfig, size: PhysicalSize<u32>, scale_factor: f64) {
+        let clamped = limits.clamp_size(size, scale_factor);
+        self.0.set((clamped != size).then_some(clamped));
+    }
+
+    /// Hands the size to ask for to `request_inner_size`, if the last resize was outside the
+    /// limits.
+    pub(crate) fn apply(&self, request_inner_size: impl FnOnce(PhysicalSize<u32>)) {
+        if let Some(clamped) = self.0.take() {
+            log::debug!("Window resized outside its limits, asking for {clamped:?}");
+            request_inner_size(clamped);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use winit::dpi::PhysicalSize;
+
+    use super::{PendingResize, WindowConfig};
+
+    #[test]
+    fn resize_storm_asks_for_the_last_clamped_size_once() {
+        let limits = WindowConfig::new().with_min_size(320.0, 240.0).with_max_size(1280.0, 720.0);
+        let pending = PendingResize::default();
+        let requests = RefCell::new(Vec::new());
+
+        for i in 0..1000 {
+            pending.resized(&limits, PhysicalSize::new(2000 - i, 100 + i), 1.0);
+        }
+        pending.apply(|size| requests.borrow_mut().push(size));
+        // nothing new came in, so the next frame asks for nothing.
+        pending.apply(|size| requests.borrow_mut().push(size));
+        assert_eq!(*requests.borrow(), [PhysicalSize::new(1001, 720)]);
+
+        // a storm that ends inside the limits asks for nothing either.
+        pending.resized(&limits, PhysicalSize::new(100, 100), 1.0);
+        pending.resized(&limits, PhysicalSize::new(800, 600), 1.0);
+        pending.apply(|size| requests.borrow_mut().push(size));
+        assert_eq!(requests.borrow().len(), 1);
+    }
+}
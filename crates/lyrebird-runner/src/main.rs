@@ -3,8 +3,12 @@
 
 #![windows_subsystem = "windows"]
 
-fn main() {
-    lyrebird_renderer::run::<lyrebird_runtime::Runtime>().unwrap();
+fn main() -> anyhow::Result<()> {
+    let Some(path) = std::env::args().nth(1) else {
+        anyhow::bail!("usage: lyrebird-runner <scene.lyr>");
+    };
+    let scene = lyrebird_runtime::SceneFile::load(path)?;
+    lyrebird_renderer::run_with::<lyrebird_runtime::Runtime>(scene)
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -1,48 +1,88 @@
 slint::include_modules!();
 
+mod scene_file;
+
+use std::cell::RefCell;
+
 use lyrebird_renderer::prelude::*;
 
-impl AppBehaviour for Runtime {
-    fn new() -> Self {
-        Self::new().unwrap()
+pub use scene_file::{AssetKind, AssetRef, Entity, SceneFile, Transform};
+
+/// Runs a [SceneFile]. The runtime is one of these in a window, and the editor embeds one
+/// so its play controls drive the same simulation the runner would.
+#[derive(Debug, Default)]
+pub struct RuntimeScene {
+    scene: SceneFile,
+    time: f64,
+}
+
+impl RuntimeScene {
+    pub fn new(scene: SceneFile) -> Self {
+        Self { scene, time: 0.0 }
     }
 
-    fn init(&mut self, _ctx: Context) {
-        // ctx.graphics.window.set_title("lyrebird runtime");
+    pub fn scene(&self) -> &SceneFile {
+        &self.scene
     }
 
-    fn update(&mut self, _ctx: Context, _dt: f64) {
-        
+    /// Total simulated time in seconds, which only moves when the scene is updated.
+    pub fn time(&self) -> f64 {
+        self.time
     }
+}
 
-    fn render(&mut self, ctx: Context, view: &wgpu::TextureView) {
+impl Scene for RuntimeScene {
+    fn init(&mut self, _ctx: &Context) {
+        log::info!("Running scene \"{}\" with {} entities", self.scene.name, self.scene.entities.len());
+    }
+
+    fn update(&mut self, _ctx: &Context, dt: f64) {
+        self.time += dt;
+    }
+
+    fn render(&mut self, ctx: &Context, view: &wgpu::TextureView) {
         let mut encoder = ctx.graphics.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
             label: Some("Render Encoder"),
         });
 
         {
-            let _render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.1,
-                            g: 0.2,
-                            b: 0.3,
-                            a: 1.0,
-                        }),
-                        store: wgpu::StoreOp::Store,
-                    },
-                    depth_slice: None,
-                })],
-                depth_stencil_attachment: None,
-                occlusion_query_set: None,
-                timestamp_writes: None,
-            });
+            let _render_pass = ctx.begin_clear_pass(&mut encoder, view);
         }
 
-        ctx.graphics.queue.submit(std::iter::once(encoder.finish()));
+        ctx.graphics.submit(encoder);
+    }
+}
+
+thread_local! {
+    // Slint components can't hold Rust state, so the scene the runtime was started with is
+    // kept here for the lifetime of the app.
+    static SCENE: RefCell<RuntimeScene> = RefCell::default();
+}
+
+impl AppBehaviour for Runtime {
+    /// The scene to run, usually from [SceneFile::load].
+    type Args = SceneFile;
+
+    fn new(scene: SceneFile) -> Self {
+        let runtime = Self::new().unwrap();
+        runtime.set_scene_name(scene.name.as_str().into());
+        SCENE.set(RuntimeScene::new(scene));
+        runtime
+    }
+
+    fn init(&mut self, ctx: &Context) {
+        SCENE.with_borrow_mut(|scene| scene.init(ctx));
+    }
+
+    fn update(&mut self, ctx: &Context, dt: f64) {
+        SCENE.with_borrow_mut(|scene| scene.update(ctx, dt));
+    }
+
+    fn render(&mut self, ctx: &Context, view: &wgpu::TextureView) {
+        SCENE.with_borrow_mut(|scene| scene.render(ctx, view));
+    }
+
+    fn viewport_image(&mut self, image: slint::Image) {
+        self.set_texture(image);
     }
-}
\ No newline at end of file
+}
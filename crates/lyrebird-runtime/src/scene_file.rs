@@ -0,0 +1,246 @@
+//! The `.lyr` scene format: the entities of a scene, where they are, and the assets they use,
+//! written as [RON](https://github.com/ron-rs/ron).
+//!
+//! ```ron
+//! (
+//!     version: 1,
+//!     name: "Courtyard",
+//!     assets: [
+//!         (id: "crate", kind: Texture, path: "textures/crate.png"),
+//!     ],
+//!     entities: [
+//!         (
+//!             name: "Crate",
+//!             transform: (translation: (0.0, 0.5, 0.0)),
+//!             assets: ["crate"],
+//!         ),
+//!     ],
+//! )
+//! ```
+//!
+//! Asset paths are relative to the scene file. Anything left out of a transform keeps its
+//! identity value.
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context as _;
+use lyrebird_renderer::prelude::glam::{Mat4, Quat, Vec3};
+use serde::{Deserialize, Serialize};
+
+/// A scene as stored in a `.lyr` file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SceneFile {
+    version: u32,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub assets: Vec<AssetRef>,
+    #[serde(default)]
+    pub entities: Vec<Entity>,
+    /// The directory asset paths are relative to, which is wherever the file was loaded from.
+    #[serde(skip)]
+    base_dir: PathBuf,
+}
+
+/// A file the scene depends on, referred to by its `id` from [Entity::assets].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssetRef {
+    pub id: String,
+    pub kind: AssetKind,
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AssetKind {
+    Texture,
+    Shader,
+    Mesh,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entity {
+    pub name: String,
+    #[serde(default)]
+    pub transform: Transform,
+    /// Ids of the [AssetRef]s this entity uses.
+    #[serde(default)]
+    pub assets: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Transform {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            translation: Vec3::ZERO,
+            rotation: Quat::IDENTITY,
+            scale: Vec3::ONE,
+        }
+    }
+}
+
+impl Transform {
+    /// The model matrix, scaling first, then rotating, then translating.
+    pub fn matrix(&self) -> Mat4 {
+        Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.translation)
+    }
+}
+
+/// Just enough of a file to tell which version it is before trying to read the rest.
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+}
+
+impl Default for SceneFile {
+    fn default() -> Self {
+        Self {
+            version: Self::VERSION,
+            name: String::new(),
+            assets: Vec::new(),
+            entities: Vec::new(),
+            base_dir: PathBuf::new(),
+        }
+    }
+}
+
+impl SceneFile {
+    /// The format version written by [Self::save]. Files of any other version are rejected, as
+    /// there are no older versions to migrate from yet.
+    pub const VERSION: u32 = 1;
+
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path).with_context(|| format!("failed to read scene {}", path.display()))?;
+
+        let header: Header =
+            ron::from_str(&source).with_context(|| format!("{} is not a lyrebird scene", path.display()))?;
+        anyhow::ensure!(
+            header.version == Self::VERSION,
+            "scene {} is format version {}, but only version {} can be read",
+            path.display(),
+            header.version,
+            Self::VERSION
+        );
+
+        let mut scene: Self = ron::from_str(&source).with_context(|| format!("failed to parse scene {}", path.display()))?;
+        scene.validate().with_context(|| format!("scene {} is invalid", path.display()))?;
+        scene.base_dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        Ok(scene)
+    }
+
+    /// Writes the scene to `path` as the current [Self::VERSION]. Asset paths are written as
+    /// they are, so they should already be relative to where the file is going.
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        self.validate()?;
+
+        let scene = Self {
+            version: Self::VERSION,
+            ..self.clone()
+        };
+        let source = ron::ser::to_string_pretty(&scene, ron::ser::PrettyConfig::default())?;
+        std::fs::write(path, source).with_context(|| format!("failed to write scene {}", path.display()))
+    }
+
+    /// The asset with `id`, if the scene has one.
+    pub fn asset(&self, id: &str) -> Option<&AssetRef> {
+        self.assets.iter().find(|asset| asset.id == id)
+    }
+
+    /// Where the asset with `id` is on disk, resolved against the scene file's directory.
+    pub fn asset_path(&self, id: &str) -> Option<PathBuf> {
+        self.asset(id).map(|asset| self.base_dir.join(&asset.path))
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        let mut ids = HashSet::new();
+        for asset in &self.assets {
+            anyhow::ensure!(ids.insert(asset.id.as_str()), "asset id \"{}\" is used more than once", asset.id);
+        }
+
+        for entity in &self.entities {
+            for id in &entity.assets {
+                anyhow::ensure!(
+                    ids.contains(id.as_str()),
+                    "entity \"{}\" uses asset \"{id}\", which the scene doesn't have",
+                    entity.name
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use lyrebird_renderer::prelude::glam::{Quat, Vec3};
+
+    use super::{AssetKind, AssetRef, Entity, SceneFile, Transform};
+
+    /// A path in the temp directory unique to `name` and this process.
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("lyrebird-{}-{name}.lyr", std::process::id()))
+    }
+
+    #[test]
+    fn save_then_load_round_trips() -> anyhow::Result<()> {
+        let mut scene = SceneFile::new("Courtyard");
+        scene.assets.push(AssetRef {
+            id: "crate".into(),
+            kind: AssetKind::Texture,
+            path: "textures/crate.png".into(),
+        });
+        scene.entities.push(Entity {
+            name: "Crate".into(),
+            transform: Transform {
+                translation: Vec3::new(0.0, 0.5, 0.0),
+                rotation: Quat::from_rotation_y(1.0),
+                scale: Vec3::splat(2.0),
+            },
+            assets: vec!["crate".into()],
+        });
+
+        let path = temp_path("round-trip");
+        scene.save(&path)?;
+        let loaded = SceneFile::load(&path);
+        std::fs::remove_file(&path)?;
+        let loaded = loaded?;
+
+        assert_eq!(loaded.name, scene.name);
+        assert_eq!(loaded.assets, scene.assets);
+        assert_eq!(loaded.entities, scene.entities);
+        assert_eq!(loaded.asset_path("crate"), Some(std::env::temp_dir().join("textures/crate.png")));
+        Ok(())
+    }
+
+    #[test]
+    fn newer_versions_are_rejected() -> anyhow::Result<()> {
+        let path = temp_path("version-2");
+        std::fs::write(&path, "(version: 2, name: \"From the future\")")?;
+        let loaded = SceneFile::load(&path);
+        std::fs::remove_file(&path)?;
+
+        let error = loaded.unwrap_err().to_string();
+        assert!(error.contains("format version 2, but only version 1 can be read"), "{error}");
+        Ok(())
+    }
+}